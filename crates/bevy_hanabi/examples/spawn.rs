@@ -39,7 +39,7 @@ fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
         .insert(image)
         .insert_bundle(ParticlesEffect::new_bundle(
             128,
-            Spawner::new(10.0, Vec3::ZERO, Vec3::new(1., 2., 3.)),
+            Spawner::new(10.0, Vec3::ZERO, Vec3::new(1., 2., 3.), 2.0),
             Updater::default(),
         ));
 }
@@ -0,0 +1,104 @@
+//! Asset-authored effect definitions, loaded from `.particle.ron` files.
+//!
+//! This lets effects be authored and hot-reloaded outside code, e.g. from a
+//! Blender or editor-driven workflow, instead of being built up by hand
+//! through [`ParticlesEffect::new_bundle`].
+//!
+//! [`ParticlesEffect::new_bundle`]: crate::ParticlesEffect::new_bundle
+
+use anyhow::Result;
+use bevy::{
+    asset::{AssetLoader, Handle, LoadContext, LoadedAsset},
+    ecs::prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Modifier, ParticlesEffect, Spawner, Updater, UpdateState};
+
+/// A serializable, asset-authored effect definition.
+///
+/// Loaded from a `.particle.ron` file via [`EffectAssetLoader`], and applied
+/// to any entity carrying a matching `Handle<EffectAsset>` by
+/// [`sync_effect_from_asset`], including on hot-reload.
+#[derive(Debug, Clone, Serialize, Deserialize, TypeUuid)]
+#[uuid = "8c7f4b1a-2e3d-4c5f-9a6e-1b2c3d4e5f6a"]
+pub struct EffectAsset {
+    /// Maximum number of particles alive at once for this effect.
+    pub capacity: usize,
+    /// Spawn parameters.
+    pub spawner: Spawner,
+    /// Update (simulation) parameters.
+    pub updater: Updater,
+    /// Ordered list of modifiers further customizing the effect.
+    #[serde(default)]
+    pub modifiers: Vec<Box<dyn Modifier>>,
+}
+
+/// Loads [`EffectAsset`] from `.particle.ron` files.
+#[derive(Default)]
+pub struct EffectAssetLoader;
+
+impl AssetLoader for EffectAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let asset: EffectAsset = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particle.ron"]
+    }
+}
+
+/// Applies the loaded [`EffectAsset`] to any entity carrying a
+/// `Handle<EffectAsset>`, (re-)inserting [`ParticlesEffect`] with the asset's
+/// spawner/updater/modifiers whenever the asset is created or hot-reloaded.
+pub fn sync_effect_from_asset(
+    mut commands: Commands,
+    effect_assets: Res<Assets<EffectAsset>>,
+    mut asset_events: EventReader<AssetEvent<EffectAsset>>,
+    mut query: Query<(
+        Entity,
+        &Handle<EffectAsset>,
+        Option<&mut ParticlesEffect>,
+        &mut UpdateState,
+    )>,
+) {
+    for event in asset_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        let asset = match effect_assets.get(handle) {
+            Some(asset) => asset,
+            None => continue,
+        };
+        for (entity, effect_handle, existing, mut state) in query.iter_mut() {
+            if effect_handle != handle {
+                continue;
+            }
+            state.set_capacity(asset.capacity);
+            match existing {
+                Some(mut effect) => {
+                    effect.set_spawner(asset.spawner);
+                    effect.set_updater(asset.updater);
+                    effect.set_modifiers(asset.modifiers.clone());
+                }
+                None => {
+                    commands.entity(entity).insert(
+                        ParticlesEffect::new(asset.spawner, asset.updater)
+                            .with_modifiers(asset.modifiers.clone()),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -99,51 +99,220 @@
 //! [`Transform`]: bevy::transform::components::Transform
 
 use bevy::{prelude::*, reflect::TypeUuid};
+use serde::{Deserialize, Serialize};
 
+mod asset;
+mod modifier;
 mod plugin;
 mod render;
 
+pub use asset::{sync_effect_from_asset, EffectAsset, EffectAssetLoader};
+pub use modifier::Modifier;
 pub use plugin::HanabiPlugin;
+pub use render::{ExtractResource, ExtractResourcePlugin};
 
-#[derive(Debug, Clone, Copy, Component, TypeUuid)]
+/// Global configuration resource for the Hanabi particle simulation.
+///
+/// This is the single authoritative place to tune simulation parameters that
+/// would otherwise be hard-coded constants scattered across the spawn and
+/// render code. It is extracted into the render world each frame it changes
+/// via [`ExtractResourcePlugin<HanabiConfig>`].
+#[derive(Debug, Clone, Copy)]
+pub struct HanabiConfig {
+    /// Acceleration applied to all particles, in world units per second
+    /// squared.
+    pub gravity: Vec3,
+}
+
+impl Default for HanabiConfig {
+    fn default() -> Self {
+        HanabiConfig {
+            gravity: Vec3::new(0., -9.81, 0.),
+        }
+    }
+}
+
+impl ExtractResource for HanabiConfig {
+    fn extract_resource(&self) -> Self {
+        *self
+    }
+}
+
+/// Color blending mode used when compositing a particle's color onto the
+/// scene, selected per [`ParticlesEffect`]. Particles only batch together
+/// with others using the same mode, since each mode specializes its own
+/// render pipeline with a different [`BlendState`](bevy::render::render_resource::BlendState).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Standard "over" alpha blending. The usual choice for opaque-looking
+    /// sprites with soft edges.
+    AlphaBlend,
+    /// Additive blending, which brightens the scene behind the particle
+    /// instead of occluding it. The usual choice for glow/fire/spark effects.
+    Additive,
+    /// Alpha blending assuming the particle's color is already premultiplied
+    /// by its alpha, avoiding a dark fringe around soft edges that plain
+    /// [`AlphaBlend`](Self::AlphaBlend) can produce.
+    PremultipliedAlpha,
+    /// Multiplicative blending, which darkens the scene behind the particle.
+    /// Useful for shadow or smoke-style effects.
+    Multiply,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::AlphaBlend
+    }
+}
+
+#[derive(Debug, Clone, Component, TypeUuid)]
 #[uuid = "c48df8b5-7eca-4d25-831e-513c2575cf6c"]
 pub struct ParticlesEffect {
     spawner: Spawner,
     updater: Updater,
+    modifiers: Vec<Box<dyn Modifier>>,
+    /// Whether particles render as camera-facing billboards in 3D
+    /// (`Transparent3d`) instead of flat 2D sprites (`Transparent2d`).
+    billboard: bool,
+    /// Color blending mode used when compositing particles onto the scene.
+    blend_mode: BlendMode,
 }
 
 impl ParticlesEffect {
+    /// Create a new effect directly from a spawner and an updater, with no
+    /// modifiers. Use [`with_modifiers`](Self::with_modifiers) to add some.
+    pub fn new(spawner: Spawner, updater: Updater) -> Self {
+        ParticlesEffect {
+            spawner,
+            updater,
+            modifiers: Vec::new(),
+            billboard: false,
+            blend_mode: BlendMode::default(),
+        }
+    }
+
+    /// Attach a modifier pipeline to this effect, e.g.
+    /// [`ColorOverLifetimeModifier`] or [`SizeOverLifetimeModifier`].
+    ///
+    /// [`ColorOverLifetimeModifier`]: crate::modifier::ColorOverLifetimeModifier
+    /// [`SizeOverLifetimeModifier`]: crate::modifier::SizeOverLifetimeModifier
+    pub fn with_modifiers(mut self, modifiers: Vec<Box<dyn Modifier>>) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Render particles as camera-facing billboards in a 3D scene, instead
+    /// of flat 2D sprites. The particle's position is kept; its quad is
+    /// re-oriented each frame to face the viewer.
+    pub fn with_billboard(mut self, billboard: bool) -> Self {
+        self.billboard = billboard;
+        self
+    }
+
+    /// Set the color blending mode used when compositing this effect's
+    /// particles onto the scene. Defaults to [`BlendMode::AlphaBlend`].
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
     pub fn new_bundle(
         capacity: usize,
         spawner: Spawner,
         updater: Updater,
     ) -> (ParticlesEffect, SpawnState, UpdateState) {
         (
-            ParticlesEffect { spawner, updater },
+            ParticlesEffect::new(spawner, updater),
             SpawnState::default(),
             UpdateState::new(capacity),
         )
     }
+
+    /// Start loading an effect definition from a [`Handle<EffectAsset>`].
+    ///
+    /// The returned bundle attaches the handle to the entity, but not
+    /// [`ParticlesEffect`] itself yet: [`sync_effect_from_asset`] inserts it
+    /// (and keeps it, and the particle buffer capacity, in sync on every
+    /// subsequent asset reload) once the asset finishes loading.
+    pub fn from_asset(handle: Handle<EffectAsset>) -> (Handle<EffectAsset>, SpawnState, UpdateState) {
+        (handle, SpawnState::default(), UpdateState::new(0))
+    }
+
+    pub(crate) fn set_spawner(&mut self, spawner: Spawner) {
+        self.spawner = spawner;
+    }
+
+    /// This effect's spawner, e.g. for the `gpu_sim` compute path to read
+    /// spawn origin/velocity when extracting per-frame simulation params.
+    pub(crate) fn spawner(&self) -> &Spawner {
+        &self.spawner
+    }
+
+    pub(crate) fn set_updater(&mut self, updater: Updater) {
+        self.updater = updater;
+    }
+
+    pub(crate) fn set_modifiers(&mut self, modifiers: Vec<Box<dyn Modifier>>) {
+        self.modifiers = modifiers;
+    }
+
+    /// Whether this effect's particles render as camera-facing billboards.
+    pub(crate) fn billboard(&self) -> bool {
+        self.billboard
+    }
+
+    /// This effect's color blending mode.
+    pub(crate) fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sample the modifier pipeline's color for a particle at normalized age
+    /// `age_ratio`, or opaque white if no modifier customizes color.
+    pub(crate) fn sample_color(&self, age_ratio: f32) -> Color {
+        self.modifiers
+            .iter()
+            .find_map(|modifier| modifier.sample_color(age_ratio))
+            .unwrap_or(Color::WHITE)
+    }
+
+    /// Sample the modifier pipeline's size for a particle at normalized age
+    /// `age_ratio`, or `fallback_size` (typically the source image's size)
+    /// if no modifier customizes size.
+    pub(crate) fn sample_size(&self, age_ratio: f32, fallback_size: Vec2) -> Vec2 {
+        self.modifiers
+            .iter()
+            .find_map(|modifier| modifier.sample_size(age_ratio))
+            .unwrap_or(fallback_size)
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Spawner {
     /// Number of particles to spawn per second.
     rate: f32,
     origin: Vec3,
     velocity: Vec3,
+    /// Lifetime, in seconds, of each spawned particle.
+    lifetime: f32,
 }
 
 impl Spawner {
-    pub fn new(rate: f32, origin: Vec3, velocity: Vec3) -> Self {
+    pub fn new(rate: f32, origin: Vec3, velocity: Vec3, lifetime: f32) -> Self {
         Spawner {
             rate,
             origin,
             velocity,
+            lifetime,
         }
     }
 
-    pub fn spawn(&mut self, spawn_state: &mut SpawnState, state: &mut UpdateState, dt: f32) {
+    pub fn spawn(
+        &mut self,
+        spawn_state: &mut SpawnState,
+        state: &mut UpdateState,
+        config: &HanabiConfig,
+        dt: f32,
+    ) {
         // Tick
         spawn_state.count += self.rate * dt;
 
@@ -154,28 +323,74 @@ impl Spawner {
         // Initialize
         if count > 0 {
             let particles = &mut state.buffer[state.used..state.used + count];
-            let acc = Vec3::new(0., -9.81, 0.);
             for p in particles {
                 p.position = self.origin;
                 p.init_velocity(self.velocity, dt);
-                p.acceleration = acc;
+                p.acceleration = config.gravity;
+                p.age = 0.0;
+                p.lifetime = self.lifetime;
             }
+            state.used += count;
         }
     }
+
+    /// Tick the spawn accumulator and return how many particles to spawn
+    /// this frame, without writing into a CPU-side particle buffer.
+    ///
+    /// Used by the `gpu_sim` compute path in place of [`Spawner::spawn`],
+    /// since GPU-simulated particles are initialized directly by the
+    /// `spawn` compute shader instead of the CPU.
+    pub(crate) fn tick_spawn_count(&self, spawn_state: &mut SpawnState, dt: f32) -> u32 {
+        spawn_state.count += self.rate * dt;
+        let count = spawn_state.count as u32;
+        spawn_state.count = spawn_state.count.fract();
+        count
+    }
+
+    /// Per-particle spawn origin, in the space the effect simulates in.
+    pub(crate) fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    /// Per-particle initial velocity.
+    pub(crate) fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Lifetime assigned to each newly-spawned particle, in seconds.
+    pub(crate) fn lifetime(&self) -> f32 {
+        self.lifetime
+    }
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct Updater {}
 
 impl Updater {
     pub fn update(&mut self, state: &mut UpdateState, dt: f32) {
         // Verlet integration
         let particles = &mut state.buffer[..state.used];
-        for p in particles {
+        for p in particles.iter_mut() {
             let prev = p.position;
             p.position = p.position * 2.0 - p.prev_position + p.acceleration * dt * dt;
             p.prev_position = prev;
+            p.age += dt;
         }
+
+        // Compact out particles that reached the end of their lifetime, so
+        // their slots are recycled: otherwise `used` only ever grows, and
+        // `Spawner::spawn`'s allocation eventually starves once the buffer
+        // fills up.
+        let mut live = 0;
+        for i in 0..state.used {
+            if state.buffer[i].age < state.buffer[i].lifetime {
+                if live != i {
+                    state.buffer.swap(live, i);
+                }
+                live += 1;
+            }
+        }
+        state.used = live;
     }
 }
 
@@ -183,6 +398,25 @@ impl Updater {
 pub struct SpawnState {
     // Fractional remainder of the number of particles to spawn.
     count: f32,
+    /// Number of particles the `gpu_sim` compute path should spawn this
+    /// frame, computed by [`Spawner::tick_spawn_count`] and extracted into
+    /// `ExtractedGpuEffect::spawn_count` for the compute shader. Unused by
+    /// the CPU path, which spawns directly via [`Spawner::spawn`].
+    gpu_spawn_count: u32,
+}
+
+impl SpawnState {
+    /// Number of particles the `gpu_sim` compute path should spawn this
+    /// frame.
+    pub(crate) fn gpu_spawn_count(&self) -> u32 {
+        self.gpu_spawn_count
+    }
+
+    /// Set the number of particles the `gpu_sim` compute path should spawn
+    /// this frame; see [`Self::gpu_spawn_count`].
+    pub(crate) fn set_gpu_spawn_count(&mut self, count: u32) {
+        self.gpu_spawn_count = count;
+    }
 }
 
 #[derive(Debug, Default, Copy, Clone, Component)]
@@ -190,6 +424,8 @@ pub struct MotionState {
     position: Vec3,
     prev_position: Vec3,
     acceleration: Vec3,
+    age: f32,
+    lifetime: f32,
 }
 
 #[derive(Debug, Clone, Component)]
@@ -207,12 +443,45 @@ impl UpdateState {
         state.buffer.resize_with(capacity, Default::default);
         state
     }
+
+    /// Resize the particle buffer to hold `capacity` particles, e.g. once an
+    /// [`EffectAsset`]'s capacity becomes known after loading.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.buffer.resize_with(capacity, Default::default);
+        self.used = self.used.min(capacity);
+    }
+
+    /// Iterate over the currently live particles.
+    pub fn iter(&self) -> impl Iterator<Item = &MotionState> {
+        self.buffer[..self.used].iter()
+    }
+
+    /// Maximum number of particles this effect can have alive at once, e.g.
+    /// for the `gpu_sim` compute path to size its GPU-side particle buffer.
+    pub(crate) fn capacity(&self) -> u32 {
+        self.buffer.len() as u32
+    }
 }
 
 impl MotionState {
     pub fn init_velocity(&mut self, velocity: Vec3, dt: f32) {
         self.prev_position = self.position - velocity * dt;
     }
+
+    /// Current particle position, in the space the effect simulates in.
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    /// Normalized particle age in `0..=1`, `0` at spawn and `1` at
+    /// end-of-life.
+    pub fn age_ratio(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
 }
 
 /// Playback state of an animator.
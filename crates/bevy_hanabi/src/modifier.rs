@@ -0,0 +1,158 @@
+//! Modifiers customizing how an effect's particles spawn and render.
+//!
+//! Modifiers are serialized as trait objects (via `typetag`) so effect
+//! authors can extend behavior from a `.particle.ron` [`EffectAsset`] without
+//! touching the loader.
+//!
+//! [`EffectAsset`]: crate::EffectAsset
+
+use std::fmt::Debug;
+
+use bevy::{math::Vec2, render::color::Color};
+use serde::{Deserialize, Serialize};
+
+use crate::Spawner;
+
+/// A single modifier applied, in order, to an effect.
+///
+/// All methods except [`clone_boxed`](Self::clone_boxed) have a no-op
+/// default, so a given modifier only needs to implement `clone_boxed` and
+/// the hooks relevant to what it customizes.
+#[typetag::serde(tag = "type")]
+pub trait Modifier: Debug + Send + Sync + 'static {
+    /// Customize the [`Spawner`] before it spawns this frame's particles,
+    /// e.g. to change the spawn shape or apply a force field.
+    fn apply_spawner(&self, _spawner: &mut Spawner) {}
+
+    /// Sample this modifier's color contribution for a particle at
+    /// normalized age `age_ratio` in `0..=1`, or `None` if this modifier
+    /// doesn't affect color.
+    fn sample_color(&self, _age_ratio: f32) -> Option<Color> {
+        None
+    }
+
+    /// Sample this modifier's size contribution for a particle at
+    /// normalized age `age_ratio` in `0..=1`, or `None` if this modifier
+    /// doesn't affect size.
+    fn sample_size(&self, _age_ratio: f32) -> Option<Vec2> {
+        None
+    }
+
+    /// Clone this modifier into a new boxed trait object. Lets a shared
+    /// [`EffectAsset`]'s modifier pipeline be copied onto each
+    /// [`ParticlesEffect`] using it.
+    ///
+    /// [`EffectAsset`]: crate::EffectAsset
+    /// [`ParticlesEffect`]: crate::ParticlesEffect
+    fn clone_boxed(&self) -> Box<dyn Modifier>;
+}
+
+impl Clone for Box<dyn Modifier> {
+    fn clone(&self) -> Self {
+        self.clone_boxed()
+    }
+}
+
+/// A value that can be linearly interpolated with another instance of
+/// itself, used as a [`Gradient`] control point.
+pub trait Lerp: Copy {
+    /// Interpolate linearly between `self` and `other` by `ratio` in
+    /// `0..=1`.
+    fn lerp(self, other: Self, ratio: f32) -> Self;
+}
+
+impl Lerp for Color {
+    fn lerp(self, other: Self, ratio: f32) -> Self {
+        self + (other - self) * ratio
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, other: Self, ratio: f32) -> Self {
+        self + (other - self) * ratio
+    }
+}
+
+/// An ordered list of `(key, value)` control points, linearly interpolated
+/// by `key`, a normalized particle age in `0..=1`.
+///
+/// Used by [`ColorOverLifetimeModifier`] and [`SizeOverLifetimeModifier`] to
+/// vary a particle's color or size over its lifetime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gradient<T> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Lerp> Gradient<T> {
+    /// Create a gradient from a set of `(key, value)` control points. Points
+    /// are sorted by `key`.
+    pub fn new(mut keys: Vec<(f32, T)>) -> Self {
+        keys.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Gradient { keys }
+    }
+
+    /// Sample the gradient at normalized age `ratio` in `0..=1`. Clamps to
+    /// the first or last control point when `ratio` falls outside the range
+    /// of keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the gradient has no control points.
+    pub fn sample(&self, ratio: f32) -> T {
+        let ratio = ratio.clamp(0.0, 1.0);
+        let (first_key, first_value) = *self.keys.first().expect("Gradient has no keys");
+        if ratio <= first_key {
+            return first_value;
+        }
+        for window in self.keys.windows(2) {
+            let (k0, v0) = window[0];
+            let (k1, v1) = window[1];
+            if ratio <= k1 {
+                let local_ratio = if k1 > k0 {
+                    (ratio - k0) / (k1 - k0)
+                } else {
+                    0.0
+                };
+                return v0.lerp(v1, local_ratio);
+            }
+        }
+        self.keys.last().expect("Gradient has no keys").1
+    }
+}
+
+/// Fades a particle's color over its lifetime according to a [`Gradient`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorOverLifetimeModifier {
+    /// Gradient sampled by the particle's normalized age.
+    pub gradient: Gradient<Color>,
+}
+
+#[typetag::serde]
+impl Modifier for ColorOverLifetimeModifier {
+    fn sample_color(&self, age_ratio: f32) -> Option<Color> {
+        Some(self.gradient.sample(age_ratio))
+    }
+
+    fn clone_boxed(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
+
+/// Shrinks or grows a particle's size over its lifetime according to a
+/// [`Gradient`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeOverLifetimeModifier {
+    /// Gradient sampled by the particle's normalized age.
+    pub gradient: Gradient<Vec2>,
+}
+
+#[typetag::serde]
+impl Modifier for SizeOverLifetimeModifier {
+    fn sample_size(&self, age_ratio: f32) -> Option<Vec2> {
+        Some(self.gradient.sample(age_ratio))
+    }
+
+    fn clone_boxed(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
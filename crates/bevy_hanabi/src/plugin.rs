@@ -1,18 +1,29 @@
 use bevy::{
-    core_pipeline::Transparent2d,
+    core_pipeline::{Transparent2d, Transparent3d},
     prelude::*,
     render::{
         render_phase::DrawFunctions, render_resource::SpecializedPipelines, RenderApp, RenderStage,
     },
 };
 
+#[cfg(feature = "gpu_sim")]
+use bevy::render::render_graph::RenderGraph;
+
 use crate::{
     render::{
         extract_particles, extract_particles_events, prepare_particles, queue_particles,
-        DrawParticles, ExtractedParticles, ImageBindGroups, ParticlesAssetEvents, ParticlesMeta,
-        ParticlesPipeline, PARTICLES_SHADER_HANDLE,
+        BindlessTextures, DrawParticles, ExtractedParticles, ImageBindGroups,
+        ParticlesAssetEvents, ParticlesMeta, ParticlesPipeline, PARTICLES_SHADER_HANDLE,
     },
-    ParticlesEffect, SpawnState, UpdateState,
+    EffectAsset, EffectAssetLoader, ExtractResourcePlugin, HanabiConfig, ParticlesEffect,
+    SpawnState, UpdateState,
+};
+use crate::sync_effect_from_asset;
+
+#[cfg(feature = "gpu_sim")]
+use crate::render::{
+    extract_gpu_effects, prepare_gpu_sim_params, prepare_particle_buffers, ParticlesComputeNode,
+    ParticlesComputePipeline,
 };
 
 /// Plugin to add systems related to Hanabi.
@@ -21,10 +32,31 @@ pub struct HanabiPlugin;
 
 impl Plugin for HanabiPlugin {
     fn build(&self, app: &mut App) {
-        // Register the spawn and update systems
+        // Register the global simulation configuration, and push it to the
+        // render world whenever it changes
+        app.init_resource::<HanabiConfig>()
+            .add_plugin(ExtractResourcePlugin::<HanabiConfig>::default());
+
+        // Register asset-authored effect definitions, loaded from
+        // `.particle.ron` files, and kept in sync with any entity using them
+        app.add_asset::<EffectAsset>()
+            .init_asset_loader::<EffectAssetLoader>()
+            .add_system(sync_effect_from_asset.system());
+
+        // Register the CPU spawn and update systems. These are superseded by
+        // the GPU compute simulation when the `gpu_sim` feature is enabled,
+        // and kept only as a fallback for platforms without compute support.
+        #[cfg(not(feature = "gpu_sim"))]
         app.add_system(hanabi_spawn.system())
             .add_system(hanabi_update.system());
 
+        // The GPU compute path still ticks its spawn accumulator on the CPU
+        // (the compute shader only receives the resulting count, and doesn't
+        // keep the fractional remainder across frames itself); `extract_gpu_effects` then forwards the
+        // resulting count into the render world each frame.
+        #[cfg(feature = "gpu_sim")]
+        app.add_system(hanabi_gpu_spawn_tick.system());
+
         // Register the particles shader
         let mut shaders = app.world.get_resource_mut::<Assets<Shader>>().unwrap();
         let sprite_shader = Shader::from_wgsl(include_str!("render/particles.wgsl"));
@@ -37,6 +69,7 @@ impl Plugin for HanabiPlugin {
         let render_app = app.sub_app_mut(RenderApp);
         render_app
             .init_resource::<ImageBindGroups>()
+            .init_resource::<BindlessTextures>()
             .init_resource::<ParticlesPipeline>()
             .init_resource::<SpecializedPipelines<ParticlesPipeline>>()
             .init_resource::<ParticlesMeta>()
@@ -50,24 +83,67 @@ impl Plugin for HanabiPlugin {
             .add_system_to_stage(RenderStage::Prepare, prepare_particles)
             .add_system_to_stage(RenderStage::Queue, queue_particles);
 
-        let draw_particles = DrawParticles::new(&mut render_app.world);
+        #[cfg(feature = "gpu_sim")]
+        {
+            render_app
+                .init_resource::<ParticlesComputePipeline>()
+                // Without this, `ExtractedGpuEffect` is never populated, and
+                // `prepare_particle_buffers`/`ParticlesComputeNode` have
+                // nothing to allocate or dispatch against: the compute path
+                // would silently simulate nothing. If you add a new
+                // `gpu_sim`-only resource that needs per-frame main-world
+                // data, extend `extract_gpu_effects` rather than adding a
+                // parallel extraction system.
+                .add_system_to_stage(RenderStage::Extract, extract_gpu_effects)
+                .add_system_to_stage(RenderStage::Prepare, prepare_particle_buffers)
+                // Runs every frame, unlike `prepare_particle_buffers` above
+                // (which only allocates once): otherwise `dt`/`spawn_count`
+                // would freeze at whatever the allocation frame saw.
+                .add_system_to_stage(RenderStage::Prepare, prepare_gpu_sim_params);
+
+            let mut render_graph = render_app.world.get_resource_mut::<RenderGraph>().unwrap();
+            render_graph.add_node("hanabi_particles_compute", ParticlesComputeNode::default());
+            render_graph
+                .add_node_edge("hanabi_particles_compute", bevy::core_pipeline::node::MAIN_PASS_DEPENDENCIES)
+                .unwrap();
+        }
+
+        // `DrawParticles` implements `Draw` for both phases, but each
+        // `DrawFunctions<P>` owns its entries, so it's registered once per
+        // phase with its own `SystemState`.
+        let draw_particles_2d = DrawParticles::new(&mut render_app.world);
         render_app
             .world
             .get_resource::<DrawFunctions<Transparent2d>>()
             .unwrap()
             .write()
-            .add(draw_particles);
+            .add(draw_particles_2d);
+
+        let draw_particles_3d = DrawParticles::new(&mut render_app.world);
+        render_app
+            .world
+            .get_resource::<DrawFunctions<Transparent3d>>()
+            .unwrap()
+            .write()
+            .add(draw_particles_3d);
     }
 }
 
 pub fn hanabi_spawn(
     time: Res<Time>,
+    config: Res<HanabiConfig>,
     mut query: Query<(&mut ParticlesEffect, &mut SpawnState, &mut UpdateState)>,
 ) {
     for (ref mut effect, ref mut spawn_state, ref mut state) in query.iter_mut() {
-        effect
-            .spawner
-            .spawn(spawn_state, state, time.delta_seconds());
+        // Let modifiers customize this frame's spawner (e.g. a force field
+        // changing the spawn shape) without mutating the effect's stored
+        // one, so any such customization is transient to the frame rather
+        // than compounding across frames.
+        let mut spawner = effect.spawner;
+        for modifier in &effect.modifiers {
+            modifier.apply_spawner(&mut spawner);
+        }
+        spawner.spawn(spawn_state, state, &config, time.delta_seconds());
     }
 }
 
@@ -76,3 +152,19 @@ pub fn hanabi_update(time: Res<Time>, mut query: Query<(&mut ParticlesEffect, &m
         effect.updater.update(motion, time.delta_seconds());
     }
 }
+
+/// Ticks the spawn accumulator for the `gpu_sim` compute path, computing how
+/// many particles the `spawn` compute shader should add this frame. See
+/// [`extract_gpu_effects`](crate::render::extract_gpu_effects), which reads
+/// the result back out of [`SpawnState`] into the render world.
+#[cfg(feature = "gpu_sim")]
+pub fn hanabi_gpu_spawn_tick(time: Res<Time>, mut query: Query<(&ParticlesEffect, &mut SpawnState)>) {
+    for (effect, mut spawn_state) in query.iter_mut() {
+        let mut spawner = effect.spawner;
+        for modifier in &effect.modifiers {
+            modifier.apply_spawner(&mut spawner);
+        }
+        let count = spawner.tick_spawn_count(&mut spawn_state, time.delta_seconds());
+        spawn_state.set_gpu_spawn_count(count);
+    }
+}
@@ -0,0 +1,506 @@
+//! GPU compute-based particle simulation.
+//!
+//! This is an alternative to the CPU Verlet integration in [`Spawner::spawn`]
+//! and [`Updater::update`], storing particle state in a GPU storage buffer
+//! and advancing it with a compute shader each frame instead of round-tripping
+//! positions through the CPU. It is gated behind the `gpu_sim` feature;
+//! platforms without compute support (e.g. some WebGL targets) should keep
+//! the CPU path enabled instead.
+//!
+//! [`Spawner::spawn`]: crate::Spawner::spawn
+//! [`Updater::update`]: crate::Updater::update
+
+use bevy::{
+    asset::{Assets, Handle, HandleUntyped},
+    core::{Pod, Time, Zeroable},
+    ecs::prelude::*,
+    math::{Vec2, Vec3, Vec4},
+    reflect::TypeUuid,
+    render::{
+        render_graph::{Node, NodeRunError, RenderGraphContext},
+        render_resource::{std140::AsStd140, *},
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::Image,
+        view::ComputedVisibility,
+        Extract,
+    },
+    transform::components::GlobalTransform,
+};
+
+use super::ParticlesPipeline;
+use crate::{BlendMode, HanabiConfig, ParticlesEffect, SpawnState, UpdateState};
+
+pub const PARTICLES_COMPUTE_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 2763343953151597127);
+
+/// GPU-side representation of a single simulated particle, std430-compatible.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct GpuParticle {
+    pub position: [f32; 3],
+    pub age: f32,
+    pub prev_position: [f32; 3],
+    pub lifetime: f32,
+    pub acceleration: [f32; 3],
+    pub _pad: f32,
+}
+
+/// Per-effect parameters uploaded once per frame to drive the compute passes.
+#[derive(Debug, Clone, Copy, AsStd140)]
+pub struct GpuSimParams {
+    pub dt: f32,
+    pub gravity: Vec3,
+    pub spawn_count: u32,
+    pub origin: Vec3,
+    pub velocity: Vec3,
+    pub capacity: u32,
+    pub lifetime: f32,
+}
+
+/// Per-effect parameters consumed directly by the vertex shader (group 2) to
+/// render GPU-simulated particles. Unlike the CPU path, the compute
+/// simulation only tracks position/age/lifetime per particle (see
+/// [`GpuParticle`]), not a full transform or a color/size-over-lifetime
+/// curve sample, so every particle in an effect shares the same constant
+/// size and color, taken from the start of [`ParticlesEffect`]'s curves.
+#[derive(Debug, Clone, Copy, AsStd140)]
+pub struct GpuRenderParams {
+    pub color: Vec4,
+    pub size: Vec2,
+}
+
+/// GPU buffers backing the compute simulation of a single [`ParticlesEffect`].
+///
+/// [`ParticlesEffect`]: crate::ParticlesEffect
+#[derive(Component)]
+pub struct GpuParticleBuffers {
+    /// Storage buffer of [`GpuParticle`], sized to `capacity`.
+    pub particle_buffer: Buffer,
+    /// Single-element atomic counter of currently-live particles, also used
+    /// to derive the index count (6 per particle) of the indirect draw call.
+    pub particle_count_buffer: Buffer,
+    /// `DrawIndexedIndirect` arguments buffer (index_count, instance_count,
+    /// first_index, base_vertex, first_instance), rewritten every frame by
+    /// the `finalize_indirect` compute pass from the final live particle
+    /// count, and consumed directly by [`DrawParticles`](super::DrawParticles)
+    /// via `draw_indexed_indirect`.
+    pub indirect_buffer: Buffer,
+    /// Uniform buffer holding this frame's [`GpuSimParams`].
+    pub sim_params_buffer: Buffer,
+    /// Uniform buffer holding this frame's [`GpuRenderParams`], read by the
+    /// vertex shader.
+    pub render_params_buffer: Buffer,
+    pub capacity: u32,
+}
+
+/// Number of `u32`/`i32` fields in a `DrawIndexedIndirectArgs` struct
+/// (index_count, instance_count, first_index, base_vertex, first_instance),
+/// matching the layout `finalize_indirect` in `particles_compute.wgsl`
+/// writes into [`GpuParticleBuffers::indirect_buffer`].
+const INDEXED_INDIRECT_ARGS_WORDS: u64 = 5;
+
+impl GpuParticleBuffers {
+    pub fn new(render_device: &RenderDevice, capacity: u32) -> Self {
+        let particle_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("hanabi_particle_buffer"),
+            size: capacity as u64 * std::mem::size_of::<GpuParticle>() as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let particle_count_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("hanabi_particle_count_buffer"),
+            size: std::mem::size_of::<u32>() as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let indirect_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("hanabi_indirect_buffer"),
+            size: INDEXED_INDIRECT_ARGS_WORDS * std::mem::size_of::<u32>() as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sim_params_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("hanabi_sim_params_buffer"),
+            size: GpuSimParams::std140_size_static() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let render_params_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("hanabi_render_params_buffer"),
+            size: GpuRenderParams::std140_size_static() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        GpuParticleBuffers {
+            particle_buffer,
+            particle_count_buffer,
+            indirect_buffer,
+            sim_params_buffer,
+            render_params_buffer,
+            capacity,
+        }
+    }
+
+    /// Upload this frame's simulation parameters. Called every frame (not
+    /// just once at buffer-creation time): `dt`/`spawn_count`/`gravity` all
+    /// change frame to frame, so only uploading on the allocation frame
+    /// would freeze `spawn_count` at (almost always) 0 and `dt` at whatever
+    /// the first frame happened to report, for the effect's whole lifetime.
+    pub fn write_sim_params(&self, render_queue: &RenderQueue, params: &GpuSimParams) {
+        render_queue.write_buffer(&self.sim_params_buffer, 0, params.as_std140().as_bytes());
+    }
+
+    /// Upload this frame's render parameters, read by the vertex shader.
+    pub fn write_render_params(&self, render_queue: &RenderQueue, params: &GpuRenderParams) {
+        render_queue.write_buffer(&self.render_params_buffer, 0, params.as_std140().as_bytes());
+    }
+}
+
+pub struct ParticlesComputePipeline {
+    pub sim_layout: BindGroupLayout,
+    pub update_pipeline: ComputePipeline,
+    pub spawn_pipeline: ComputePipeline,
+    /// Derives the frame's `DrawIndexedIndirectArgs` from the final live
+    /// particle count; see `finalize_indirect` in `particles_compute.wgsl`.
+    pub finalize_pipeline: ComputePipeline,
+}
+
+impl FromWorld for ParticlesComputePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.get_resource::<RenderDevice>().unwrap();
+
+        let sim_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("hanabi_sim_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(GpuSimParams::std140_size_static() as u64),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(
+                            std::mem::size_of::<GpuParticle>() as u64
+                        ),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<u32>() as u64),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(
+                            INDEXED_INDIRECT_ARGS_WORDS * std::mem::size_of::<u32>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let shader_module = render_device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("hanabi_compute_shader"),
+            source: ShaderSource::Wgsl(include_str!("particles_compute.wgsl").into()),
+        });
+
+        let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("hanabi_compute_pipeline_layout"),
+            bind_group_layouts: &[&sim_layout],
+            push_constant_ranges: &[],
+        });
+
+        let update_pipeline = render_device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("hanabi_update_pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader_module,
+            entry_point: "update",
+        });
+        let spawn_pipeline = render_device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("hanabi_spawn_pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader_module,
+            entry_point: "spawn",
+        });
+        let finalize_pipeline = render_device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("hanabi_finalize_indirect_pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader_module,
+            entry_point: "finalize_indirect",
+        });
+
+        ParticlesComputePipeline {
+            sim_layout,
+            update_pipeline,
+            spawn_pipeline,
+            finalize_pipeline,
+        }
+    }
+}
+
+/// Number of particles advanced or spawned per compute workgroup; must match
+/// `@workgroup_size` in `particles_compute.wgsl`.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Number of workgroups needed to cover `count` invocations.
+pub fn dispatch_size(count: u32) -> u32 {
+    (count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE
+}
+
+/// Bind group over a single effect's [`GpuParticleBuffers`], built once the
+/// buffers are allocated.
+#[derive(Component)]
+pub struct GpuSimBindGroup {
+    pub bind_group: BindGroup,
+    pub capacity: u32,
+}
+
+/// Bind group over a single effect's particle storage buffer and constant
+/// [`GpuRenderParams`], read directly by the vertex shader (group 2) in
+/// place of the CPU instance buffer [`prepare_particles`](super::prepare_particles)
+/// builds for the CPU-simulated path. Built alongside [`GpuSimBindGroup`],
+/// once the buffers are allocated.
+#[derive(Component)]
+pub struct GpuRenderBindGroup {
+    pub bind_group: BindGroup,
+}
+
+/// Allocates GPU buffers and the simulation/render bind groups for every
+/// extracted effect that doesn't have them yet. Per-frame parameter uploads
+/// happen separately, in [`prepare_gpu_sim_params`]. The components are
+/// inserted through [`Commands`], so that system only sees buffers allocated
+/// here once the stage's commands have been applied.
+pub fn prepare_particle_buffers(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    compute_pipeline: Res<ParticlesComputePipeline>,
+    particles_pipeline: Res<ParticlesPipeline>,
+    query: Query<(Entity, &ExtractedGpuEffect), Without<GpuSimBindGroup>>,
+) {
+    for (entity, extracted) in query.iter() {
+        let buffers = GpuParticleBuffers::new(&render_device, extracted.capacity);
+
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            label: Some("hanabi_sim_bind_group"),
+            layout: &compute_pipeline.sim_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: buffers.sim_params_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: buffers.particle_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: buffers.particle_count_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: buffers.indirect_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let render_bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            label: Some("hanabi_gpu_render_bind_group"),
+            layout: particles_pipeline.gpu_instance_layout(),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: buffers.particle_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: buffers.render_params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        commands
+            .entity(entity)
+            .insert(buffers)
+            .insert(GpuSimBindGroup {
+                bind_group,
+                capacity: extracted.capacity,
+            })
+            .insert(GpuRenderBindGroup {
+                bind_group: render_bind_group,
+            });
+    }
+}
+
+/// Uploads this frame's [`GpuSimParams`]/[`GpuRenderParams`] for every
+/// GPU-simulated effect that has buffers. Buffers that
+/// [`prepare_particle_buffers`] allocates in the same stage are inserted
+/// through commands, and are not visible to this query until those are
+/// applied at the end of the stage. Runs unconditionally every frame: `dt`, `spawn_count` and `gravity` all change
+/// frame to frame, so gating this on `Without<GpuSimBindGroup>` like buffer
+/// allocation would freeze the simulation after the first frame.
+pub fn prepare_gpu_sim_params(
+    render_queue: Res<RenderQueue>,
+    query: Query<(&ExtractedGpuEffect, &GpuParticleBuffers)>,
+) {
+    for (extracted, buffers) in query.iter() {
+        buffers.write_sim_params(
+            &render_queue,
+            &GpuSimParams {
+                dt: extracted.dt,
+                gravity: extracted.gravity,
+                spawn_count: extracted.spawn_count,
+                origin: extracted.origin,
+                velocity: extracted.velocity,
+                capacity: extracted.capacity,
+                lifetime: extracted.lifetime,
+            },
+        );
+        buffers.write_render_params(
+            &render_queue,
+            &GpuRenderParams {
+                color: extracted.color,
+                size: extracted.size,
+            },
+        );
+    }
+}
+
+/// Extracted per-effect data driving the compute-based simulation this frame.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ExtractedGpuEffect {
+    pub dt: f32,
+    pub gravity: Vec3,
+    pub spawn_count: u32,
+    pub origin: Vec3,
+    pub velocity: Vec3,
+    pub capacity: u32,
+    pub lifetime: f32,
+    /// World-space position of the effect, used to sort its draw relative
+    /// to other 3D transparent geometry the same way billboarded
+    /// CPU-simulated batches are (see `ParticlesBatch::world_position`).
+    pub world_position: Vec3,
+    /// Constant particle color, sampled once from the effect's color curve;
+    /// see [`GpuRenderParams`] for why this isn't resampled per-particle.
+    pub color: Vec4,
+    /// Constant particle size, sampled once from the effect's size curve.
+    pub size: Vec2,
+    /// Blend mode used to composite this effect's particles.
+    pub blend_mode: BlendMode,
+}
+
+/// Extracts one [`ExtractedGpuEffect`] per visible GPU-simulated effect, so
+/// [`prepare_particle_buffers`] and [`ParticlesComputeNode`] have something
+/// to allocate buffers for and dispatch against, and so
+/// [`queue_particles`](super::queue_particles) can enqueue a draw reading
+/// back from the resulting compute buffers. Without this, the compute path
+/// never runs and nothing is ever drawn: a `gpu_sim` effect would be
+/// entirely inert. Pairs with [`crate::plugin::hanabi_gpu_spawn_tick`],
+/// which ticks the CPU-side spawn accumulator that this system just
+/// forwards.
+pub fn extract_gpu_effects(
+    mut commands: Commands,
+    config: Extract<Res<HanabiConfig>>,
+    time: Extract<Res<Time>>,
+    images: Extract<Res<Assets<Image>>>,
+    query: Extract<
+        Query<(
+            Entity,
+            &ComputedVisibility,
+            &ParticlesEffect,
+            &SpawnState,
+            &UpdateState,
+            &GlobalTransform,
+            &Handle<Image>,
+        )>,
+    >,
+) {
+    for (entity, computed_visibility, effect, spawn_state, state, transform, handle) in query.iter() {
+        if !computed_visibility.is_visible {
+            continue;
+        }
+        let fallback_size = match images.get(handle) {
+            Some(image) => {
+                let size = image.texture_descriptor.size;
+                Vec2::new(size.width as f32, size.height as f32)
+            }
+            None => Vec2::ONE,
+        };
+        let c = effect.sample_color(0.0).as_linear_rgba_f32();
+        commands
+            .get_or_spawn(entity)
+            .insert(ExtractedGpuEffect {
+                dt: time.delta_seconds(),
+                gravity: config.gravity,
+                spawn_count: spawn_state.gpu_spawn_count(),
+                origin: effect.spawner().origin(),
+                velocity: effect.spawner().velocity(),
+                capacity: state.capacity(),
+                lifetime: effect.spawner().lifetime(),
+                world_position: transform.translation,
+                color: Vec4::new(c[0], c[1], c[2], c[3]),
+                size: effect.sample_size(0.0, fallback_size),
+                blend_mode: effect.blend_mode(),
+            })
+            .insert(handle.clone_weak());
+    }
+}
+
+/// Render graph node dispatching the update, spawn and indirect-args-
+/// finalize compute passes for every GPU-simulated effect, ahead of the
+/// draw phases that read the resulting particle and indirect-draw buffers.
+#[derive(Default)]
+pub struct ParticlesComputeNode;
+
+impl Node for ParticlesComputeNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let compute_pipeline = world.resource::<ParticlesComputePipeline>();
+
+        let mut pass = render_context
+            .command_encoder
+            .begin_compute_pass(&ComputePassDescriptor::default());
+
+        for bind_group in world.query::<&GpuSimBindGroup>().iter(world) {
+            pass.set_bind_group(0, &bind_group.bind_group, &[]);
+
+            pass.set_pipeline(&compute_pipeline.update_pipeline);
+            pass.dispatch(dispatch_size(bind_group.capacity), 1, 1);
+
+            pass.set_pipeline(&compute_pipeline.spawn_pipeline);
+            pass.dispatch(dispatch_size(bind_group.capacity), 1, 1);
+
+            // Must run after both passes above have settled on this frame's
+            // final live particle count; dispatched with a single workgroup
+            // since it only derives the indirect draw's arguments once, not
+            // once per particle.
+            pass.set_pipeline(&compute_pipeline.finalize_pipeline);
+            pass.dispatch(1, 1, 1);
+        }
+
+        Ok(())
+    }
+}
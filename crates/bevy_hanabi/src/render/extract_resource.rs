@@ -0,0 +1,43 @@
+use bevy::{
+    app::{App, Plugin},
+    ecs::{change_detection::DetectChanges, prelude::*},
+    render::{RenderApp, RenderStage},
+};
+use std::marker::PhantomData;
+
+use super::Extract;
+
+/// A [`Resource`] that can be cloned from the main world into the render
+/// world each frame it changes.
+///
+/// Implement this on a main-world resource and register it with
+/// [`ExtractResourcePlugin`] to make an up-to-date copy available as a
+/// render-world resource, instead of hard-coding the values it carries into
+/// the systems that need them.
+pub trait ExtractResource: Resource {
+    /// Clone the data to extract into the render world.
+    fn extract_resource(&self) -> Self;
+}
+
+/// Plugin extracting a [`Resource`] `R` from the main world into the render
+/// world, each frame it was added or changed.
+pub struct ExtractResourcePlugin<R: ExtractResource>(PhantomData<R>);
+
+impl<R: ExtractResource> Default for ExtractResourcePlugin<R> {
+    fn default() -> Self {
+        ExtractResourcePlugin(PhantomData)
+    }
+}
+
+impl<R: ExtractResource> Plugin for ExtractResourcePlugin<R> {
+    fn build(&self, app: &mut App) {
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.add_system_to_stage(RenderStage::Extract, extract_resource::<R>);
+    }
+}
+
+fn extract_resource<R: ExtractResource>(mut commands: Commands, resource: Extract<Res<R>>) {
+    if resource.is_added() || resource.is_changed() {
+        commands.insert_resource(resource.extract_resource());
+    }
+}
@@ -1,17 +1,17 @@
-use std::{cmp::Ordering, ops::Range};
+use std::{cmp::Ordering, num::NonZeroU32, ops::Range};
 
-use crate::ParticlesEffect;
+use crate::{BlendMode, ParticlesEffect, UpdateState};
 
 use bevy::{
     asset::{AssetEvent, Assets, Handle, HandleUntyped},
     core::{FloatOrd, Pod, Zeroable},
-    core_pipeline::Transparent2d,
+    core_pipeline::{Transparent2d, Transparent3d},
     ecs::{
         prelude::*,
         system::{lifetimeless::*, SystemState},
     },
     log::trace,
-    math::{const_vec3, Mat4, Vec2, Vec3, Vec4Swizzles},
+    math::{Mat4, Vec2, Vec3, Vec4},
     reflect::TypeUuid,
     render::{
         color::Color,
@@ -20,20 +20,59 @@ use bevy::{
         render_resource::{std140::AsStd140, *},
         renderer::{RenderDevice, RenderQueue},
         texture::{BevyDefault, Image},
-        view::{ComputedVisibility, ViewUniform, ViewUniformOffset, ViewUniforms},
-        RenderWorld,
+        view::{ComputedVisibility, ExtractedView, ViewUniform, ViewUniformOffset, ViewUniforms},
+        Extract,
     },
     sprite::Rect,
     transform::components::GlobalTransform,
     utils::HashMap,
 };
 
+mod extract_resource;
+#[cfg(feature = "gpu_sim")]
+mod compute;
+
+pub use extract_resource::{ExtractResource, ExtractResourcePlugin};
+#[cfg(feature = "gpu_sim")]
+pub use compute::{
+    extract_gpu_effects, prepare_gpu_sim_params, prepare_particle_buffers, ExtractedGpuEffect,
+    GpuParticle, GpuParticleBuffers, GpuRenderBindGroup, GpuSimBindGroup, GpuSimParams,
+    ParticlesComputeNode, ParticlesComputePipeline,
+};
+
 pub const PARTICLES_SHADER_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 2763343953151597126);
 
+/// Maximum number of distinct particle textures bound at once by the
+/// bindless material layout. Effects using more than this many distinct
+/// textures in a single frame share the last slot.
+const MAX_BINDLESS_TEXTURES: u32 = 16;
+
 pub struct ParticlesPipeline {
     view_layout: BindGroupLayout,
+    /// Single-texture material layout, used as a fallback when the render
+    /// device doesn't support non-uniform indexing into a texture binding
+    /// array.
     material_layout: BindGroupLayout,
+    /// Bindless material layout, binding up to [`MAX_BINDLESS_TEXTURES`]
+    /// textures and samplers at once so particles using different textures
+    /// can still share a single batch and draw call.
+    bindless_material_layout: BindGroupLayout,
+    /// Layout of the group binding the per-instance particle records as a
+    /// read-only storage buffer; the vertex shader looks up its instance
+    /// through an index decoded from `@builtin(vertex_index)` instead of a
+    /// traditional stepped vertex/instance attribute.
+    instance_layout: BindGroupLayout,
+    /// Equivalent of `instance_layout` for the `gpu_sim` path: binds the
+    /// compute simulation's particle storage buffer directly (instead of
+    /// the CPU-built `ParticleInstance` buffer) plus the effect's constant
+    /// [`compute::GpuRenderParams`].
+    #[cfg(feature = "gpu_sim")]
+    gpu_instance_layout: BindGroupLayout,
+    /// Whether `bindless_material_layout` can actually be used, i.e. the
+    /// render device reports
+    /// `SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING`.
+    bindless_textures: bool,
 }
 
 impl FromWorld for ParticlesPipeline {
@@ -77,58 +116,226 @@ impl FromWorld for ParticlesPipeline {
             label: Some("particles_material_layout"),
         });
 
+        let bindless_material_layout =
+            render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Texture {
+                            multisampled: false,
+                            sample_type: TextureSampleType::Float { filterable: true },
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: NonZeroU32::new(MAX_BINDLESS_TEXTURES),
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                        count: NonZeroU32::new(MAX_BINDLESS_TEXTURES),
+                    },
+                ],
+                label: Some("particles_bindless_material_layout"),
+            });
+
+        let bindless_textures = render_device
+            .features()
+            .contains(Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING);
+
+        let instance_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("particles_instance_layout"),
+        });
+
+        #[cfg(feature = "gpu_sim")]
+        let gpu_instance_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(
+                            std::mem::size_of::<compute::GpuParticle>() as u64
+                        ),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(
+                            compute::GpuRenderParams::std140_size_static() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("particles_gpu_instance_layout"),
+        });
+
         ParticlesPipeline {
             view_layout,
             material_layout,
+            bindless_material_layout,
+            instance_layout,
+            #[cfg(feature = "gpu_sim")]
+            gpu_instance_layout,
+            bindless_textures,
         }
     }
 }
 
+impl ParticlesPipeline {
+    /// Layout of the group binding the compute-simulated particle buffer and
+    /// its constant render parameters directly (group 2), used by the
+    /// `gpu_sim` pipeline variant in place of `instance_layout`.
+    #[cfg(feature = "gpu_sim")]
+    pub(crate) fn gpu_instance_layout(&self) -> &BindGroupLayout {
+        &self.gpu_instance_layout
+    }
+}
+
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 pub struct ParticlesPipelineKey {
     colored: bool,
+    /// Selects the bindless material layout and `BINDLESS` shader def, so
+    /// the fragment shader indexes its texture with the instance's
+    /// `tex_index` instead of sampling a single bound texture.
+    bindless: bool,
+    /// Selects the `BILLBOARD` shader def, which re-orients each particle
+    /// quad to face the camera using the view's right/up basis vectors
+    /// instead of the instance's own rotation, and enables depth testing
+    /// for the `Transparent3d` phase.
+    billboard: bool,
+    /// Selects the color target's `BlendState`, so particles using
+    /// different blend modes (e.g. additive glow vs. standard alpha
+    /// blending) each get their own specialized pipeline.
+    blend_mode: BlendMode,
+    /// Selects the `GPU_SIM` shader def and `gpu_instance_layout`, so the
+    /// vertex shader reads particles directly from the compute simulation's
+    /// storage buffer instead of the CPU-built instance buffer. Implies
+    /// `billboard` (GPU-simulated particles carry no rotation) but is kept
+    /// as its own flag since it also changes which group-2 layout is bound.
+    #[cfg(feature = "gpu_sim")]
+    gpu_sim: bool,
 }
 
 impl SpecializedPipeline for ParticlesPipeline {
     type Key = ParticlesPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
-        let mut vertex_buffer_layout = VertexBufferLayout {
-            array_stride: 20,
-            step_mode: VertexStepMode::Vertex,
-            attributes: vec![
-                VertexAttribute {
-                    format: VertexFormat::Float32x3,
-                    offset: 0,
-                    shader_location: 0,
-                },
-                VertexAttribute {
-                    format: VertexFormat::Float32x2,
-                    offset: 12,
-                    shader_location: 1,
-                },
-            ],
-        };
-
         let mut shader_defs = Vec::new();
 
-        // Key: COLORED
+        // Key: COLORED -- selects the instance record layout with a packed
+        // color, read directly from the instance storage buffer.
         if key.colored {
             shader_defs.push("COLORED".to_string());
-            vertex_buffer_layout.attributes.push(VertexAttribute {
-                format: VertexFormat::Uint32,
-                offset: 20,
-                shader_location: 2,
-            });
-            vertex_buffer_layout.array_stride += 4;
         }
 
+        // Key: BINDLESS -- selects the texture binding array so particles
+        // using different textures can still share a single batch.
+        if key.bindless {
+            shader_defs.push("BINDLESS".to_string());
+        }
+
+        // Key: BILLBOARD -- selects camera-facing quad expansion in the
+        // vertex shader, for rendering into the 3D `Transparent3d` phase.
+        if key.billboard {
+            shader_defs.push("BILLBOARD".to_string());
+        }
+
+        // Key: GPU_SIM -- selects the vertex shader branch reading directly
+        // from the compute simulation's particle buffer. Takes priority
+        // over BILLBOARD in the shader (see particles.wgsl), so both defs
+        // being present is harmless.
+        #[cfg(feature = "gpu_sim")]
+        if key.gpu_sim {
+            shader_defs.push("GPU_SIM".to_string());
+        }
+
+        let material_layout = if key.bindless {
+            self.bindless_material_layout.clone()
+        } else {
+            self.material_layout.clone()
+        };
+
+        #[cfg(feature = "gpu_sim")]
+        let instance_layout = if key.gpu_sim {
+            self.gpu_instance_layout.clone()
+        } else {
+            self.instance_layout.clone()
+        };
+        #[cfg(not(feature = "gpu_sim"))]
+        let instance_layout = self.instance_layout.clone();
+
+        // Key: blend_mode -- selects the color target's blend state, so
+        // e.g. additive glow particles never compete with standard
+        // alpha-blended ones in the same draw.
+        let blend = Some(match key.blend_mode {
+            BlendMode::AlphaBlend => BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+            BlendMode::PremultipliedAlpha => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+            },
+            BlendMode::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::Zero,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+        });
+
         RenderPipelineDescriptor {
             vertex: VertexState {
                 shader: PARTICLES_SHADER_HANDLE.typed::<Shader>(),
                 entry_point: "vertex".into(),
                 shader_defs: shader_defs.clone(),
-                buffers: vec![vertex_buffer_layout],
+                // No vertex buffers: the quad corner and instance index are
+                // both decoded from the index buffer's resolved
+                // `vertex_index`, and the instance transform/uv/color are
+                // read from the instance storage buffer (group 2).
+                buffers: vec![],
             },
             fragment: Some(FragmentState {
                 shader: PARTICLES_SHADER_HANDLE.typed::<Shader>(),
@@ -136,11 +343,11 @@ impl SpecializedPipeline for ParticlesPipeline {
                 entry_point: "fragment".into(),
                 targets: vec![ColorTargetState {
                     format: TextureFormat::bevy_default(),
-                    blend: Some(BlendState::ALPHA_BLENDING),
+                    blend,
                     write_mask: ColorWrites::ALL,
                 }],
             }),
-            layout: Some(vec![self.view_layout.clone(), self.material_layout.clone()]),
+            layout: Some(vec![self.view_layout.clone(), material_layout, instance_layout]),
             primitive: PrimitiveState {
                 front_face: FrontFace::Ccw,
                 cull_mode: None,
@@ -150,7 +357,20 @@ impl SpecializedPipeline for ParticlesPipeline {
                 topology: PrimitiveTopology::TriangleList,
                 strip_index_format: None,
             },
-            depth_stencil: None,
+            // Billboard particles render into the 3D `Transparent3d` phase,
+            // which reads (but doesn't write) the scene depth buffer so
+            // they're correctly occluded by opaque geometry.
+            depth_stencil: if key.billboard {
+                Some(DepthStencilState {
+                    format: TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare: CompareFunction::Greater,
+                    stencil: StencilState::default(),
+                    bias: DepthBiasState::default(),
+                })
+            } else {
+                None
+            },
             multisample: MultisampleState {
                 count: 4, // TODO: use Msaa
                 mask: !0,
@@ -166,6 +386,11 @@ pub struct ExtractedParticle {
     pub color: Color,
     pub rect: Rect,
     pub handle: Handle<Image>,
+    /// Whether this particle renders as a camera-facing billboard in the 3D
+    /// `Transparent3d` phase instead of a flat 2D sprite.
+    pub billboard: bool,
+    /// Color blending mode used when compositing this particle.
+    pub blend_mode: BlendMode,
 }
 
 #[derive(Default)]
@@ -179,12 +404,9 @@ pub struct ParticlesAssetEvents {
 }
 
 pub fn extract_particles_events(
-    mut render_world: ResMut<RenderWorld>,
-    mut image_events: EventReader<AssetEvent<Image>>,
+    mut events: ResMut<ParticlesAssetEvents>,
+    mut image_events: Extract<EventReader<AssetEvent<Image>>>,
 ) {
-    let mut events = render_world
-        .get_resource_mut::<ParticlesAssetEvents>()
-        .unwrap();
     let ParticlesAssetEvents { ref mut images } = *events;
     images.clear();
 
@@ -205,88 +427,192 @@ pub fn extract_particles_events(
 }
 
 pub fn extract_particles(
-    mut render_world: ResMut<RenderWorld>,
-    images: Res<Assets<Image>>,
-    particles_query: Query<(
-        &ComputedVisibility,
-        &ParticlesEffect,
-        &GlobalTransform,
-        &Handle<Image>,
-    )>,
+    mut extracted_particles: ResMut<ExtractedParticles>,
+    images: Extract<Res<Assets<Image>>>,
+    particles_query: Extract<
+        Query<(
+            &ComputedVisibility,
+            &ParticlesEffect,
+            &GlobalTransform,
+            &Handle<Image>,
+            &UpdateState,
+        )>,
+    >,
 ) {
-    let mut extracted_particles = render_world
-        .get_resource_mut::<ExtractedParticles>()
-        .unwrap();
     extracted_particles.particles.clear();
     trace!("extract_particles");
-    for (computed_visibility, effect, transform, handle) in particles_query.iter() {
+    for (computed_visibility, effect, transform, handle, state) in particles_query.iter() {
         if !computed_visibility.is_visible {
             continue;
         }
-        if let Some(image) = images.get(handle) {
-            let size = image.texture_descriptor.size;
+        let image = match images.get(handle) {
+            Some(image) => image,
+            None => continue,
+        };
+        let size = image.texture_descriptor.size;
+        let fallback_size = Vec2::new(size.width as f32, size.height as f32);
 
+        for particle in state.iter() {
+            let age_ratio = particle.age_ratio();
             extracted_particles.particles.push(ExtractedParticle {
-                color: Color::RED, //effect.color,
-                transform: transform.compute_matrix(),
+                color: effect.sample_color(age_ratio),
+                transform: transform.compute_matrix()
+                    * Mat4::from_translation(particle.position()),
                 rect: Rect {
                     min: Vec2::ZERO,
-                    max: Vec2::new(0.2, 0.2), // effect
-                                              //.custom_size
-                                              //.unwrap_or_else(|| Vec2::new(size.width as f32, size.height as f32)),
+                    max: effect.sample_size(age_ratio, fallback_size),
                 },
                 handle: handle.clone_weak(),
+                billboard: effect.billboard(),
+                blend_mode: effect.blend_mode(),
             });
-        };
+        }
     }
 }
 
+/// Per-particle instance record read by the vertex shader from the instance
+/// storage buffer (group 2), instead of a per-vertex/per-instance vertex
+/// attribute. The transform is stored transposed, as three rows of the
+/// affine 3x4 part of the world matrix (the implicit fourth row is always
+/// `(0, 0, 0, 1)`), so the quad corner expansion happens on the GPU rather
+/// than being computed per-vertex on the CPU.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
-struct ParticlesVertex {
-    pub position: [f32; 3],
-    pub uv: [f32; 2],
+struct ParticleInstance {
+    pub transform: [Vec4; 3],
+    pub uv_offset_scale: Vec4,
+    /// World-space quad size, in `xy`. `z` holds the bindless texture-array
+    /// index (as an exactly-representable `f32`, cast back to `u32` in the
+    /// shader); `w` unused. Packed into the otherwise-unused lanes of `size`
+    /// (rather than added as its own field) so every field of the instance
+    /// record keeps the same 16-byte alignment, leaving the struct free of
+    /// implicit padding that `#[derive(Pod)]` would reject.
+    pub size: Vec4,
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
-struct ColoredParticlesVertex {
-    pub position: [f32; 3],
-    pub uv: [f32; 2],
-    pub color: u32,
+struct ColoredParticleInstance {
+    pub transform: [Vec4; 3],
+    pub uv_offset_scale: Vec4,
+    pub size: Vec4,
+    pub color: Vec4,
 }
 
 pub struct ParticlesMeta {
-    vertices: BufferVec<ParticlesVertex>,
-    colored_vertices: BufferVec<ColoredParticlesVertex>,
+    instances: BufferVec<ParticleInstance>,
+    colored_instances: BufferVec<ColoredParticleInstance>,
+    /// Shared index buffer encoding, for each of the 6 indices of a quad's
+    /// two triangles, the corner in its low 2 bits and the instance index in
+    /// the remaining bits. Built once and reused across frames; only grown
+    /// (never shrunk) when more instances are drawn than it currently
+    /// covers.
+    quad_index_buffer: BufferVec<u32>,
     view_bind_group: Option<BindGroup>,
+    instance_bind_group: Option<BindGroup>,
+    colored_instance_bind_group: Option<BindGroup>,
+    /// Shared bindless material bind group, rebuilt each frame from
+    /// whichever images [`BindlessTextures`] assigned slots to. Only used
+    /// when the pipeline was specialized with `ParticlesPipelineKey {
+    /// bindless: true, .. }`.
+    bindless_material_bind_group: Option<BindGroup>,
 }
 
 impl Default for ParticlesMeta {
     fn default() -> Self {
         Self {
-            vertices: BufferVec::new(BufferUsages::VERTEX),
-            colored_vertices: BufferVec::new(BufferUsages::VERTEX),
+            instances: BufferVec::new(BufferUsages::STORAGE),
+            colored_instances: BufferVec::new(BufferUsages::STORAGE),
+            quad_index_buffer: BufferVec::new(BufferUsages::INDEX),
             view_bind_group: None,
+            instance_bind_group: None,
+            colored_instance_bind_group: None,
+            bindless_material_bind_group: None,
         }
     }
 }
 
-const QUAD_VERTEX_POSITIONS: &[Vec3] = &[
-    const_vec3!([-0.5, -0.5, 0.0]),
-    const_vec3!([0.5, 0.5, 0.0]),
-    const_vec3!([-0.5, 0.5, 0.0]),
-    const_vec3!([-0.5, -0.5, 0.0]),
-    const_vec3!([0.5, -0.5, 0.0]),
-    const_vec3!([0.5, 0.5, 0.0]),
-];
+/// Corner order of the two triangles making up a particle quad, matching the
+/// previous CPU-expanded winding (bottom-left, top-right, top-left,
+/// bottom-left, bottom-right, top-right), packed in the low 2 bits of each
+/// index buffer entry.
+const QUAD_CORNERS: [u32; 6] = [0b00, 0b11, 0b10, 0b00, 0b01, 0b11];
+
+/// Grow the shared quad index buffer, if needed, so it covers at least
+/// `instance_count` particles (6 indices each).
+fn ensure_quad_index_buffer(
+    quad_index_buffer: &mut BufferVec<u32>,
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    instance_count: u32,
+) {
+    let current_instances = quad_index_buffer.len() as u32 / 6;
+    if instance_count <= current_instances {
+        return;
+    }
+    for instance_index in current_instances..instance_count {
+        for corner in QUAD_CORNERS {
+            quad_index_buffer.push((instance_index << 2) | corner);
+        }
+    }
+    quad_index_buffer.write_buffer(render_device, render_queue);
+}
 
 #[derive(Component)]
 pub struct ParticlesBatch {
     range: Range<u32>,
-    handle: Handle<Image>,
+    /// Single texture shared by every particle in this batch. Only set for
+    /// the non-bindless fallback path, which still splits batches by
+    /// texture identity; `None` for bindless batches, which select their
+    /// texture per-particle via the instance's packed `tex_index` instead.
+    handle: Option<Handle<Image>>,
     z: f32,
+    /// World-space position of the last particle added to this batch. Used
+    /// by billboarded batches to compute a true camera-space distance (see
+    /// `queue_particles`); `z` alone isn't enough since it discards the
+    /// particle's X/Y and would otherwise implicitly assume the camera sits
+    /// at the world origin.
+    world_position: Vec3,
     colored: bool,
+    /// Whether this batch renders into the 3D `Transparent3d` phase as
+    /// camera-facing billboards, instead of `Transparent2d`.
+    billboard: bool,
+    /// Blend mode shared by every particle in this batch; particles only
+    /// batch with others using the same mode, since each specializes its own
+    /// pipeline `BlendState`.
+    blend_mode: BlendMode,
+}
+
+/// Stable per-image texture-array slot indices assigned while batching
+/// particles in bindless mode, so [`queue_particles`] can build a single
+/// array bind group whose layout matches the `tex_index` baked into each
+/// instance by [`prepare_particles`]. Cleared and reassigned every frame.
+#[derive(Default)]
+pub struct BindlessTextures {
+    indices: HashMap<Handle<Image>, u32>,
+    images: Vec<Handle<Image>>,
+}
+
+impl BindlessTextures {
+    fn clear(&mut self) {
+        self.indices.clear();
+        self.images.clear();
+    }
+
+    /// Look up (or assign) the texture-array slot for `handle`. Once
+    /// [`MAX_BINDLESS_TEXTURES`] distinct images are in use this frame,
+    /// further new images share the last slot rather than growing further.
+    fn index_of(&mut self, handle: &Handle<Image>) -> u32 {
+        if let Some(&index) = self.indices.get(handle) {
+            return index;
+        }
+        let index = (self.images.len() as u32).min(MAX_BINDLESS_TEXTURES - 1);
+        if self.images.len() < MAX_BINDLESS_TEXTURES as usize {
+            self.images.push(handle.clone_weak());
+        }
+        self.indices.insert(handle.clone_weak(), index);
+        index
+    }
 }
 
 pub fn prepare_particles(
@@ -295,9 +621,14 @@ pub fn prepare_particles(
     render_queue: Res<RenderQueue>,
     mut particles_meta: ResMut<ParticlesMeta>,
     mut extracted_particles: ResMut<ExtractedParticles>,
+    mut bindless_textures: ResMut<BindlessTextures>,
+    particles_pipeline: Res<ParticlesPipeline>,
+    #[cfg(feature = "gpu_sim")] gpu_effects: Query<&compute::ExtractedGpuEffect>,
 ) {
-    particles_meta.vertices.clear();
-    particles_meta.colored_vertices.clear();
+    particles_meta.instances.clear();
+    particles_meta.colored_instances.clear();
+    bindless_textures.clear();
+    let bindless = particles_pipeline.bindless_textures;
 
     // sort first by z and then by handle. this ensures that, when possible, batches span multiple z layers
     // batches won't span z-layers if there is another batch between them
@@ -314,26 +645,44 @@ pub fn prepare_particles(
     let mut colored_end = 0;
     let mut current_batch_handle: Option<Handle<Image>> = None;
     let mut current_batch_colored = false;
+    let mut current_batch_billboard = false;
+    let mut current_batch_blend_mode = BlendMode::default();
     let mut last_z = 0.0;
+    let mut last_position = Vec3::ZERO;
     for extracted_sprite in extracted_particles.particles.iter() {
         let colored = extracted_sprite.color != Color::WHITE;
+        let billboard = extracted_sprite.billboard;
+        let blend_mode = extracted_sprite.blend_mode;
         if let Some(current_batch_handle) = &current_batch_handle {
-            if *current_batch_handle != extracted_sprite.handle || current_batch_colored != colored
+            // In bindless mode, particles using different textures can
+            // still share a batch; only the colored/billboard/blend_mode
+            // flags split it.
+            let same_texture = bindless || *current_batch_handle == extracted_sprite.handle;
+            if !same_texture
+                || current_batch_colored != colored
+                || current_batch_billboard != billboard
+                || current_batch_blend_mode != blend_mode
             {
                 if current_batch_colored {
                     commands.spawn_bundle((ParticlesBatch {
                         range: colored_start..colored_end,
-                        handle: current_batch_handle.clone_weak(),
+                        handle: (!bindless).then_some(current_batch_handle.clone_weak()),
                         z: last_z,
+                        world_position: last_position,
                         colored: true,
+                        billboard: current_batch_billboard,
+                        blend_mode: current_batch_blend_mode,
                     },));
                     colored_start = colored_end;
                 } else {
                     commands.spawn_bundle((ParticlesBatch {
                         range: start..end,
-                        handle: current_batch_handle.clone_weak(),
+                        handle: (!bindless).then_some(current_batch_handle.clone_weak()),
                         z: last_z,
+                        world_position: last_position,
                         colored: false,
+                        billboard: current_batch_billboard,
+                        blend_mode: current_batch_blend_mode,
                     },));
                     start = end;
                 }
@@ -341,56 +690,55 @@ pub fn prepare_particles(
         }
         current_batch_handle = Some(extracted_sprite.handle.clone_weak());
         current_batch_colored = colored;
+        current_batch_billboard = billboard;
+        current_batch_blend_mode = blend_mode;
         let sprite_rect = extracted_sprite.rect;
 
-        // Specify the corners of the sprite
-        let mut bottom_left = Vec2::new(sprite_rect.min.x, sprite_rect.max.y);
-        let mut top_left = sprite_rect.min;
-        let mut top_right = Vec2::new(sprite_rect.max.x, sprite_rect.min.y);
-        let mut bottom_right = sprite_rect.max;
-
-        let uvs: [[f32; 2]; 6] = [
-            bottom_left.into(),
-            top_right.into(),
-            top_left.into(),
-            bottom_left.into(),
-            bottom_right.into(),
-            top_right.into(),
+        let uv_offset_scale = Vec4::new(
+            sprite_rect.min.x,
+            sprite_rect.min.y,
+            sprite_rect.size().x,
+            sprite_rect.size().y,
+        );
+        let tex_index = if bindless {
+            bindless_textures.index_of(&extracted_sprite.handle)
+        } else {
+            0
+        };
+        let size = sprite_rect.size();
+        let size = Vec4::new(size.x, size.y, tex_index as f32, 0.0);
+        // Transpose the world matrix into three rows, so the vertex shader
+        // can reconstruct the affine transform and expand the quad corner
+        // itself, instead of the CPU expanding all 6 vertices up front.
+        let transform = [
+            extracted_sprite.transform.row(0),
+            extracted_sprite.transform.row(1),
+            extracted_sprite.transform.row(2),
         ];
 
-        let rect_size = extracted_sprite.rect.size().extend(1.0);
         if current_batch_colored {
-            let color = extracted_sprite.color.as_linear_rgba_f32();
-            // encode color as a single u32 to save space
-            let color = (color[0] * 255.0) as u32
-                | ((color[1] * 255.0) as u32) << 8
-                | ((color[2] * 255.0) as u32) << 16
-                | ((color[3] * 255.0) as u32) << 24;
-            for (index, vertex_position) in QUAD_VERTEX_POSITIONS.iter().enumerate() {
-                let mut final_position = *vertex_position * rect_size;
-                final_position = (extracted_sprite.transform * final_position.extend(1.0)).xyz();
-                particles_meta.colored_vertices.push(ColoredParticlesVertex {
-                    position: final_position.into(),
-                    uv: uvs[index],
-                    color,
-                });
-            }
+            let c = extracted_sprite.color.as_linear_rgba_f32();
+            let color = Vec4::new(c[0], c[1], c[2], c[3]);
+            particles_meta.colored_instances.push(ColoredParticleInstance {
+                transform,
+                uv_offset_scale,
+                size,
+                color,
+            });
         } else {
-            for (index, vertex_position) in QUAD_VERTEX_POSITIONS.iter().enumerate() {
-                let mut final_position = *vertex_position * rect_size;
-                final_position = (extracted_sprite.transform * final_position.extend(1.0)).xyz();
-                particles_meta.vertices.push(ParticlesVertex {
-                    position: final_position.into(),
-                    uv: uvs[index],
-                });
-            }
+            particles_meta.instances.push(ParticleInstance {
+                transform,
+                uv_offset_scale,
+                size,
+            });
         }
 
         last_z = extracted_sprite.transform.w_axis[2];
+        last_position = extracted_sprite.transform.w_axis.truncate();
         if current_batch_colored {
-            colored_end += QUAD_VERTEX_POSITIONS.len() as u32;
+            colored_end += 1;
         } else {
-            end += QUAD_VERTEX_POSITIONS.len() as u32;
+            end += 1;
         }
     }
 
@@ -399,27 +747,52 @@ pub fn prepare_particles(
         if let Some(current_batch_handle) = current_batch_handle {
             commands.spawn_bundle((ParticlesBatch {
                 range: start..end,
-                handle: current_batch_handle,
+                handle: (!bindless).then_some(current_batch_handle),
                 colored: false,
                 z: last_z,
+                world_position: last_position,
+                billboard: current_batch_billboard,
+                blend_mode: current_batch_blend_mode,
             },));
         }
     } else if colored_start != colored_end {
         if let Some(current_batch_handle) = current_batch_handle {
             commands.spawn_bundle((ParticlesBatch {
                 range: colored_start..colored_end,
-                handle: current_batch_handle,
+                handle: (!bindless).then_some(current_batch_handle),
                 colored: true,
                 z: last_z,
+                world_position: last_position,
+                billboard: current_batch_billboard,
+                blend_mode: current_batch_blend_mode,
             },));
         }
     }
 
+    // The shared quad index buffer must also cover the largest capacity of
+    // any GPU-simulated effect: those draws read straight from the compute
+    // buffers and never appear in the CPU batches counted by `end`/
+    // `colored_end` above, but still decode their instance index the same
+    // way from this same index buffer.
+    #[cfg(feature = "gpu_sim")]
+    let max_instances = gpu_effects
+        .iter()
+        .map(|effect| effect.capacity)
+        .fold(end.max(colored_end), u32::max);
+    #[cfg(not(feature = "gpu_sim"))]
+    let max_instances = end.max(colored_end);
+
+    ensure_quad_index_buffer(
+        &mut particles_meta.quad_index_buffer,
+        &render_device,
+        &render_queue,
+        max_instances,
+    );
     particles_meta
-        .vertices
+        .instances
         .write_buffer(&render_device, &render_queue);
     particles_meta
-        .colored_vertices
+        .colored_instances
         .write_buffer(&render_device, &render_queue);
 }
 
@@ -430,7 +803,8 @@ pub struct ImageBindGroups {
 
 #[allow(clippy::too_many_arguments)]
 pub fn queue_particles(
-    draw_functions: Res<DrawFunctions<Transparent2d>>,
+    draw_functions_2d: Res<DrawFunctions<Transparent2d>>,
+    draw_functions_3d: Res<DrawFunctions<Transparent3d>>,
     render_device: Res<RenderDevice>,
     mut particles_meta: ResMut<ParticlesMeta>,
     view_uniforms: Res<ViewUniforms>,
@@ -438,10 +812,19 @@ pub fn queue_particles(
     mut pipelines: ResMut<SpecializedPipelines<ParticlesPipeline>>,
     mut pipeline_cache: ResMut<RenderPipelineCache>,
     mut image_bind_groups: ResMut<ImageBindGroups>,
+    bindless_textures: Res<BindlessTextures>,
     gpu_images: Res<RenderAssets<Image>>,
     mut sprite_batches: Query<(Entity, &ParticlesBatch)>,
-    mut views: Query<&mut RenderPhase<Transparent2d>>,
+    mut views_2d: Query<&mut RenderPhase<Transparent2d>>,
+    mut views_3d: Query<(&ExtractedView, &mut RenderPhase<Transparent3d>)>,
     events: Res<ParticlesAssetEvents>,
+    #[cfg(feature = "gpu_sim")] gpu_particles: Query<(
+        Entity,
+        &compute::GpuParticleBuffers,
+        &compute::GpuRenderBindGroup,
+        &compute::ExtractedGpuEffect,
+        Option<&Handle<Image>>,
+    )>,
 ) {
     // If an image has changed, the GpuImage has (probably) changed
     for event in &events.images {
@@ -461,52 +844,198 @@ pub fn queue_particles(
             label: Some("particles_view_bind_group"),
             layout: &particles_pipeline.view_layout,
         }));
-        let draw_particles_function = draw_functions.read().get_id::<DrawParticles>().unwrap();
-        let pipeline = pipelines.specialize(
-            &mut pipeline_cache,
-            &particles_pipeline,
-            ParticlesPipelineKey { colored: false },
-        );
-        let colored_pipeline = pipelines.specialize(
-            &mut pipeline_cache,
-            &particles_pipeline,
-            ParticlesPipelineKey { colored: true },
-        );
-        for mut transparent_phase in views.iter_mut() {
+        if let Some(instances_buffer) = particles_meta.instances.buffer() {
+            particles_meta.instance_bind_group =
+                Some(render_device.create_bind_group(&BindGroupDescriptor {
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: instances_buffer.as_entire_binding(),
+                    }],
+                    label: Some("particles_instance_bind_group"),
+                    layout: &particles_pipeline.instance_layout,
+                }));
+        }
+        if let Some(colored_instances_buffer) = particles_meta.colored_instances.buffer() {
+            particles_meta.colored_instance_bind_group =
+                Some(render_device.create_bind_group(&BindGroupDescriptor {
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: colored_instances_buffer.as_entire_binding(),
+                    }],
+                    label: Some("particles_colored_instance_bind_group"),
+                    layout: &particles_pipeline.instance_layout,
+                }));
+        }
+        let bindless = particles_pipeline.bindless_textures;
+        if bindless && !bindless_textures.images.is_empty() {
+            let mut texture_views: Vec<_> = bindless_textures
+                .images
+                .iter()
+                .map(|handle| &gpu_images.get(handle).unwrap().texture_view)
+                .collect();
+            let mut samplers: Vec<_> = bindless_textures
+                .images
+                .iter()
+                .map(|handle| &gpu_images.get(handle).unwrap().sampler)
+                .collect();
+            // The layout always declares `count: MAX_BINDLESS_TEXTURES`
+            // regardless of how many distinct images are actually in use
+            // this frame: wgpu requires a bound array's length to match its
+            // layout's declared count unless the unsupported
+            // PARTIALLY_BOUND_BINDING_ARRAY feature is requested, so pad out
+            // with repeats of the last real entry rather than leaving the
+            // array short.
+            while texture_views.len() < MAX_BINDLESS_TEXTURES as usize {
+                texture_views.push(*texture_views.last().unwrap());
+                samplers.push(*samplers.last().unwrap());
+            }
+            particles_meta.bindless_material_bind_group =
+                Some(render_device.create_bind_group(&BindGroupDescriptor {
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureViewArray(&texture_views),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::SamplerArray(&samplers),
+                        },
+                    ],
+                    label: Some("particles_bindless_material_bind_group"),
+                    layout: &particles_pipeline.bindless_material_layout,
+                }));
+        }
+
+        // Ensures a batch's single-texture material bind group (non-bindless
+        // fallback only) exists, inserting it on first use.
+        let mut ensure_material_bind_group = |handle: &Handle<Image>| {
+            image_bind_groups
+                .values
+                .entry(handle.clone_weak())
+                .or_insert_with(|| {
+                    let gpu_image = gpu_images.get(handle).unwrap();
+                    render_device.create_bind_group(&BindGroupDescriptor {
+                        entries: &[
+                            BindGroupEntry {
+                                binding: 0,
+                                resource: BindingResource::TextureView(&gpu_image.texture_view),
+                            },
+                            BindGroupEntry {
+                                binding: 1,
+                                resource: BindingResource::Sampler(&gpu_image.sampler),
+                            },
+                        ],
+                        label: Some("particles_material_bind_group"),
+                        layout: &particles_pipeline.material_layout,
+                    })
+                });
+        };
+
+        // Each batch is specialized individually: with `blend_mode` now part
+        // of the key alongside `colored`/`billboard`, there are too many
+        // live combinations to usefully precompute up front, and
+        // `SpecializedPipelines` already caches by key internally.
+        let draw_particles_function_2d = draw_functions_2d.read().get_id::<DrawParticles>().unwrap();
+        for mut transparent_phase in views_2d.iter_mut() {
             for (entity, batch) in sprite_batches.iter_mut() {
-                image_bind_groups
-                    .values
-                    .entry(batch.handle.clone_weak())
-                    .or_insert_with(|| {
-                        let gpu_image = gpu_images.get(&batch.handle).unwrap();
-                        render_device.create_bind_group(&BindGroupDescriptor {
-                            entries: &[
-                                BindGroupEntry {
-                                    binding: 0,
-                                    resource: BindingResource::TextureView(&gpu_image.texture_view),
-                                },
-                                BindGroupEntry {
-                                    binding: 1,
-                                    resource: BindingResource::Sampler(&gpu_image.sampler),
-                                },
-                            ],
-                            label: Some("particles_material_bind_group"),
-                            layout: &particles_pipeline.material_layout,
-                        })
-                    });
-                transparent_phase.add(Transparent2d {
-                    draw_function: draw_particles_function,
-                    pipeline: if batch.colored {
-                        colored_pipeline
-                    } else {
-                        pipeline
+                if batch.billboard {
+                    continue;
+                }
+                if let Some(handle) = &batch.handle {
+                    ensure_material_bind_group(handle);
+                }
+                let pipeline = pipelines.specialize(
+                    &mut pipeline_cache,
+                    &particles_pipeline,
+                    ParticlesPipelineKey {
+                        colored: batch.colored,
+                        bindless,
+                        billboard: false,
+                        blend_mode: batch.blend_mode,
+                        #[cfg(feature = "gpu_sim")]
+                        gpu_sim: false,
                     },
+                );
+                transparent_phase.add(Transparent2d {
+                    draw_function: draw_particles_function_2d,
+                    pipeline,
                     entity,
                     sort_key: FloatOrd(batch.z),
                     batch_range: None,
                 });
             }
         }
+
+        if !views_3d.is_empty() {
+            let draw_particles_function_3d =
+                draw_functions_3d.read().get_id::<DrawParticles>().unwrap();
+            for (view, mut transparent_phase) in views_3d.iter_mut() {
+                let view_position = view.transform.translation;
+                for (entity, batch) in sprite_batches.iter_mut() {
+                    if !batch.billboard {
+                        continue;
+                    }
+                    if let Some(handle) = &batch.handle {
+                        ensure_material_bind_group(handle);
+                    }
+                    let pipeline = pipelines.specialize(
+                        &mut pipeline_cache,
+                        &particles_pipeline,
+                        ParticlesPipelineKey {
+                            colored: batch.colored,
+                            bindless,
+                            billboard: true,
+                            blend_mode: batch.blend_mode,
+                            #[cfg(feature = "gpu_sim")]
+                            gpu_sim: false,
+                        },
+                    );
+                    // Batches only track a single world-space position per
+                    // batch (see `prepare_particles`), so this is a coarse,
+                    // per-batch approximation of camera distance rather than
+                    // a true per-particle sort; it does use the batch's full
+                    // world position, not just its Z, so it isn't skewed by
+                    // the camera sitting away from the world origin.
+                    let distance = view_position.distance(batch.world_position);
+                    transparent_phase.add(Transparent3d {
+                        distance,
+                        draw_function: draw_particles_function_3d,
+                        pipeline,
+                        entity,
+                    });
+                }
+
+                // GPU-simulated effects never produce a `ParticlesBatch`
+                // (there's no CPU-side instance buffer to batch into): each
+                // extracted effect gets its own draw reading straight from
+                // its compute buffers via `draw_indexed_indirect`, since the
+                // live particle count is only known on the GPU.
+                #[cfg(feature = "gpu_sim")]
+                for (entity, _buffers, _render_bind_group, extracted, handle) in gpu_particles.iter() {
+                    if let Some(handle) = handle {
+                        ensure_material_bind_group(handle);
+                    }
+                    let pipeline = pipelines.specialize(
+                        &mut pipeline_cache,
+                        &particles_pipeline,
+                        ParticlesPipelineKey {
+                            colored: false,
+                            bindless: false,
+                            billboard: true,
+                            blend_mode: extracted.blend_mode,
+                            gpu_sim: true,
+                        },
+                    );
+                    let distance = view_position.distance(extracted.world_position);
+                    transparent_phase.add(Transparent3d {
+                        distance,
+                        draw_function: draw_particles_function_3d,
+                        pipeline,
+                        entity,
+                    });
+                }
+            }
+        }
     }
 }
 
@@ -518,48 +1047,162 @@ pub struct DrawParticles {
         SQuery<Read<ViewUniformOffset>>,
         SQuery<Read<ParticlesBatch>>,
     )>,
+    /// GPU-simulated effects don't have a `ParticlesBatch` (see
+    /// `queue_particles`), so they're resolved through this separate
+    /// `SystemState` instead, kept as its own struct field (rather than a
+    /// tuple element above) since `compute::GpuParticleBuffers` et al. only
+    /// exist under this feature.
+    #[cfg(feature = "gpu_sim")]
+    gpu_params: SystemState<
+        SQuery<(
+            Read<compute::GpuParticleBuffers>,
+            Read<compute::GpuRenderBindGroup>,
+            Read<Handle<Image>>,
+        )>,
+    >,
 }
 
 impl DrawParticles {
     pub fn new(world: &mut World) -> Self {
         Self {
             params: SystemState::new(world),
+            #[cfg(feature = "gpu_sim")]
+            gpu_params: SystemState::new(world),
         }
     }
 }
 
-impl Draw<Transparent2d> for DrawParticles {
-    fn draw<'w>(
+impl DrawParticles {
+    /// Shared drawing logic for both the 2D (`Transparent2d`) and 3D
+    /// (`Transparent3d`) phases: the two `Draw` impls below only differ in
+    /// which phase item they're given, so they resolve the view uniform
+    /// offset, pipeline and batch entity themselves and delegate here.
+    fn draw_batch<'w>(
         &mut self,
         world: &'w World,
         pass: &mut TrackedRenderPass<'w>,
         view: Entity,
-        item: &Transparent2d,
+        pipeline_id: CachedPipelineId,
+        batch_entity: Entity,
     ) {
         let (particles_meta, image_bind_groups, pipelines, views, particles) = self.params.get(world);
         let view_uniform = views.get(view).unwrap();
         let particles_meta = particles_meta.into_inner();
         let image_bind_groups = image_bind_groups.into_inner();
-        let sprite_batch = particles.get(item.entity).unwrap();
-        if let Some(pipeline) = pipelines.into_inner().get(item.pipeline) {
+        let pipelines = pipelines.into_inner();
+
+        let sprite_batch = match particles.get(batch_entity) {
+            Ok(sprite_batch) => sprite_batch,
+            // Not a CPU batch: must be a GPU-simulated effect instead.
+            #[cfg(feature = "gpu_sim")]
+            Err(_) => {
+                return self.draw_gpu_batch(
+                    world,
+                    pass,
+                    view_uniform,
+                    pipeline_id,
+                    batch_entity,
+                    particles_meta,
+                    image_bind_groups,
+                    pipelines,
+                );
+            }
+            #[cfg(not(feature = "gpu_sim"))]
+            Err(_) => return,
+        };
+        if let Some(pipeline) = pipelines.get(pipeline_id) {
             pass.set_render_pipeline(pipeline);
-            if sprite_batch.colored {
-                pass.set_vertex_buffer(0, particles_meta.colored_vertices.buffer().unwrap().slice(..));
+            pass.set_index_buffer(
+                particles_meta.quad_index_buffer.buffer().unwrap().slice(..),
+                0,
+                IndexFormat::Uint32,
+            );
+            let instance_bind_group = if sprite_batch.colored {
+                particles_meta.colored_instance_bind_group.as_ref().unwrap()
             } else {
-                pass.set_vertex_buffer(0, particles_meta.vertices.buffer().unwrap().slice(..));
-            }
+                particles_meta.instance_bind_group.as_ref().unwrap()
+            };
+            // A batch with no `handle` is bindless: every particle in it
+            // selects its own texture via `tex_index`, so group 1 binds the
+            // shared texture array instead of a single per-batch texture.
+            let material_bind_group = match &sprite_batch.handle {
+                Some(handle) => image_bind_groups.values.get(handle).unwrap(),
+                None => particles_meta.bindless_material_bind_group.as_ref().unwrap(),
+            };
             pass.set_bind_group(
                 0,
                 particles_meta.view_bind_group.as_ref().unwrap(),
                 &[view_uniform.offset],
             );
+            pass.set_bind_group(1, material_bind_group, &[]);
+            pass.set_bind_group(2, instance_bind_group, &[]);
+
+            let first_index = sprite_batch.range.start * 6;
+            let index_count = sprite_batch.range.len() as u32 * 6;
+            pass.draw_indexed(first_index..(first_index + index_count), 0, 0..1);
+        }
+    }
+
+    /// Draws a single GPU-simulated effect, reading its instance data
+    /// straight from the compute buffers instead of `ParticlesMeta`'s
+    /// CPU-built instance buffer. The live particle count isn't known on
+    /// the CPU (it's only ever updated by the compute passes), so this
+    /// issues an indirect draw sized from `GpuParticleBuffers::indirect_buffer`,
+    /// which `finalize_indirect` rewrites every frame from the final count.
+    #[cfg(feature = "gpu_sim")]
+    fn draw_gpu_batch<'w>(
+        &mut self,
+        world: &'w World,
+        pass: &mut TrackedRenderPass<'w>,
+        view_uniform: &ViewUniformOffset,
+        pipeline_id: CachedPipelineId,
+        batch_entity: Entity,
+        particles_meta: &'w ParticlesMeta,
+        image_bind_groups: &'w ImageBindGroups,
+        pipelines: &'w RenderPipelineCache,
+    ) {
+        let query = self.gpu_params.get(world);
+        let (buffers, render_bind_group, handle) = query.get(batch_entity).unwrap();
+        if let Some(pipeline) = pipelines.get(pipeline_id) {
+            pass.set_render_pipeline(pipeline);
+            pass.set_index_buffer(
+                particles_meta.quad_index_buffer.buffer().unwrap().slice(..),
+                0,
+                IndexFormat::Uint32,
+            );
+            let material_bind_group = image_bind_groups.values.get(handle).unwrap();
             pass.set_bind_group(
-                1,
-                image_bind_groups.values.get(&sprite_batch.handle).unwrap(),
-                &[],
+                0,
+                particles_meta.view_bind_group.as_ref().unwrap(),
+                &[view_uniform.offset],
             );
-
-            pass.draw(sprite_batch.range.clone(), 0..1);
+            pass.set_bind_group(1, material_bind_group, &[]);
+            pass.set_bind_group(2, &render_bind_group.bind_group, &[]);
+            pass.draw_indexed_indirect(&buffers.indirect_buffer, 0);
         }
     }
 }
+
+impl Draw<Transparent2d> for DrawParticles {
+    fn draw<'w>(
+        &mut self,
+        world: &'w World,
+        pass: &mut TrackedRenderPass<'w>,
+        view: Entity,
+        item: &Transparent2d,
+    ) {
+        self.draw_batch(world, pass, view, item.pipeline, item.entity);
+    }
+}
+
+impl Draw<Transparent3d> for DrawParticles {
+    fn draw<'w>(
+        &mut self,
+        world: &'w World,
+        pass: &mut TrackedRenderPass<'w>,
+        view: Entity,
+        item: &Transparent3d,
+    ) {
+        self.draw_batch(world, pass, view, item.pipeline, item.entity);
+    }
+}
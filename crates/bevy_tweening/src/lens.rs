@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 
 /// A lens over a subset of a component.
@@ -87,11 +89,40 @@ pub struct TransformRotationLens {
 
 impl Lens<Transform> for TransformRotationLens {
     fn lerp(&mut self, target: &mut Transform, ratio: f32) {
-        let value = self.start + (self.end - self.start) * ratio;
-        target.rotation = value;
+        target.rotation = slerp(self.start, self.end, ratio);
     }
 }
 
+/// Spherical linear interpolation between two (assumed normalized)
+/// quaternions, taking the shortest path between them.
+///
+/// Falls back to a normalized linear interpolation when the endpoints are
+/// nearly identical, to avoid dividing by `sin(theta) ≈ 0`.
+fn slerp(start: Quat, end: Quat, ratio: f32) -> Quat {
+    let start = start.normalize();
+    let mut end = end.normalize();
+
+    let mut dot = start.dot(end);
+    // Take the short path: flip one endpoint if the quaternions are more
+    // than 90° apart.
+    if dot < 0.0 {
+        end = -end;
+        dot = -dot;
+    }
+
+    const DOT_THRESHOLD: f32 = 0.9995;
+    if dot > DOT_THRESHOLD {
+        return (start + (end - start) * ratio).normalize();
+    }
+
+    let theta_0 = dot.acos();
+    let theta = theta_0 * ratio;
+    let sin_theta_0 = theta_0.sin();
+    let s0 = (theta_0 - theta).sin() / sin_theta_0;
+    let s1 = theta.sin() / sin_theta_0;
+    start * s0 + end * s1
+}
+
 /// A lens to manipulate the [`scale`] field of a [`Transform`] component.
 ///
 /// [`scale`]: bevy::transform::components::Transform::scale
@@ -179,3 +210,87 @@ impl Lens<Sprite> for SpriteColorLens {
         target.color = value;
     }
 }
+
+/// One sub-lens of a [`Tracks`] composite lens, together with the time span
+/// (relative to the whole animation) over which it plays.
+struct TrackEntry<T> {
+    lens: Box<dyn Lens<T> + Send + Sync + 'static>,
+    start: Duration,
+    duration: Duration,
+}
+
+/// A composite lens combining several lenses, each with its own duration,
+/// so a single [`Animator<T>`] can drive them all instead of requiring one
+/// animator per animated field.
+///
+/// [`Animator<T>`]: crate::Animator
+pub struct Tracks<T> {
+    tracks: Vec<TrackEntry<T>>,
+    total_duration: Duration,
+}
+
+impl<T> Tracks<T> {
+    /// Combine lenses that all play in parallel, in lock-step, over the same
+    /// `duration`. Use this to animate e.g. position and rotation together.
+    pub fn parallel(
+        duration: Duration,
+        lenses: Vec<Box<dyn Lens<T> + Send + Sync + 'static>>,
+    ) -> Self {
+        let tracks = lenses
+            .into_iter()
+            .map(|lens| TrackEntry {
+                lens,
+                start: Duration::ZERO,
+                duration,
+            })
+            .collect();
+        Tracks {
+            tracks,
+            total_duration: duration,
+        }
+    }
+
+    /// Chain lenses to play one after another, each for its own duration.
+    pub fn sequence(lenses: Vec<(Duration, Box<dyn Lens<T> + Send + Sync + 'static>)>) -> Self {
+        let mut start = Duration::ZERO;
+        let mut tracks = Vec::with_capacity(lenses.len());
+        for (duration, lens) in lenses {
+            tracks.push(TrackEntry {
+                lens,
+                start,
+                duration,
+            });
+            start += duration;
+        }
+        Tracks {
+            tracks,
+            total_duration: start,
+        }
+    }
+
+    /// Total duration spanning every track, to drive the [`Animator`]'s own
+    /// [`TweeningType`] duration.
+    ///
+    /// [`Animator`]: crate::Animator
+    /// [`TweeningType`]: crate::TweeningType
+    pub fn total_duration(&self) -> Duration {
+        self.total_duration
+    }
+}
+
+impl<T> Lens<T> for Tracks<T> {
+    fn lerp(&mut self, target: &mut T, ratio: f32) {
+        if self.total_duration.is_zero() {
+            return;
+        }
+        let elapsed = self.total_duration.mul_f32(ratio.clamp(0.0, 1.0));
+        for track in &mut self.tracks {
+            if track.duration.is_zero() || elapsed < track.start {
+                continue;
+            }
+            let local_elapsed = (elapsed - track.start).min(track.duration);
+            let local_ratio = local_elapsed.as_secs_f32() / track.duration.as_secs_f32();
+            track.lens.lerp(target, local_ratio);
+        }
+    }
+}
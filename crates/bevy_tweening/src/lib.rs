@@ -0,0 +1,479 @@
+#![deny(
+    //warnings,
+    missing_copy_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    //unused_import_braces,
+    unused_qualifications,
+    //missing_docs
+)]
+
+//! Tweening animation plugin for the Bevy game engine.
+//!
+//! This library provides interpolation-based ("tweening") animation for the
+//! Bevy game engine, for both components and assets.
+//!
+//! # Example
+//!
+//! Add the tweening plugin to the app:
+//!
+//! ```rust
+//! # use bevy::prelude::*;
+//! # use bevy_tweening::*;
+//! App::default()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugin(TweeningPlugin)
+//!     .run();
+//! ```
+//!
+//! Components such as the [`translation`] of a [`Transform`] are animated by
+//! inserting an [`Animator`] with a lens like [`TransformPositionLens`].
+//!
+//! [`translation`]: bevy::transform::components::Transform::translation
+//! [`Transform`]: bevy::transform::components::Transform
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+mod lens;
+mod plugin;
+
+pub use lens::*;
+pub use plugin::{asset_animator_system, component_animator_system, TweeningPlugin};
+
+/// Playback state of an [`Animator`] or [`AssetAnimator`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AnimatorState {
+    /// The animation is playing.
+    Playing,
+    /// The animation is paused/stopped.
+    Paused,
+}
+
+impl std::ops::Not for AnimatorState {
+    type Output = AnimatorState;
+
+    fn not(self) -> Self::Output {
+        match self {
+            AnimatorState::Paused => AnimatorState::Playing,
+            AnimatorState::Playing => AnimatorState::Paused,
+        }
+    }
+}
+
+/// Direction a [`TweeningType::PingPong`] animation is currently playing in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TweeningDirection {
+    Forward,
+    Backward,
+}
+
+impl std::ops::Not for TweeningDirection {
+    type Output = TweeningDirection;
+
+    fn not(self) -> Self::Output {
+        match self {
+            TweeningDirection::Forward => TweeningDirection::Backward,
+            TweeningDirection::Backward => TweeningDirection::Forward,
+        }
+    }
+}
+
+/// How an animation repeats once it reaches the end of its lens.
+#[derive(Debug, Clone, Copy)]
+pub enum TweeningType {
+    /// Run the animation once from start to end, then stop.
+    Once {
+        /// Animation duration.
+        duration: Duration,
+    },
+    /// Loop the animation forever, restarting from the start each time the
+    /// end is reached, with an optional pause before restarting.
+    Loop {
+        /// Animation duration.
+        duration: Duration,
+        /// Optional pause before restarting.
+        pause: Option<Duration>,
+    },
+    /// Loop the animation forever, reversing direction each time either end
+    /// is reached, with an optional pause at each end.
+    PingPong {
+        /// Animation duration of a single leg (start to end, or end to start).
+        duration: Duration,
+        /// Optional pause at each end before reversing.
+        pause: Option<Duration>,
+    },
+}
+
+/// Easing function to apply to the linear ratio of an animation, selecting
+/// the curve used to interpolate between the start and end values.
+///
+/// All variants implement the standard Robert Penner easing equations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EaseFunction {
+    QuadraticIn,
+    QuadraticOut,
+    QuadraticInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuarticIn,
+    QuarticOut,
+    QuarticInOut,
+    QuinticIn,
+    QuinticOut,
+    QuinticInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    CircularIn,
+    CircularOut,
+    CircularInOut,
+    ExponentialIn,
+    ExponentialOut,
+    ExponentialInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl EaseFunction {
+    /// Apply the easing curve to a linear ratio in `[0, 1]`, returning the
+    /// eased ratio.
+    fn ease(&self, x: f32) -> f32 {
+        use std::f32::consts::PI;
+        match self {
+            EaseFunction::QuadraticIn => x * x,
+            EaseFunction::QuadraticOut => x * (2.0 - x),
+            EaseFunction::QuadraticInOut => {
+                if x < 0.5 {
+                    2.0 * x * x
+                } else {
+                    -1.0 + (4.0 - 2.0 * x) * x
+                }
+            }
+            EaseFunction::CubicIn => x * x * x,
+            EaseFunction::CubicOut => {
+                let f = x - 1.0;
+                f * f * f + 1.0
+            }
+            EaseFunction::CubicInOut => {
+                if x < 0.5 {
+                    4.0 * x * x * x
+                } else {
+                    let f = 2.0 * x - 2.0;
+                    0.5 * f * f * f + 1.0
+                }
+            }
+            EaseFunction::QuarticIn => x * x * x * x,
+            EaseFunction::QuarticOut => {
+                let f = x - 1.0;
+                1.0 - f * f * f * f
+            }
+            EaseFunction::QuarticInOut => {
+                if x < 0.5 {
+                    8.0 * x * x * x * x
+                } else {
+                    let f = x - 1.0;
+                    1.0 - 8.0 * f * f * f * f
+                }
+            }
+            EaseFunction::QuinticIn => x * x * x * x * x,
+            EaseFunction::QuinticOut => {
+                let f = x - 1.0;
+                1.0 + f * f * f * f * f
+            }
+            EaseFunction::QuinticInOut => {
+                if x < 0.5 {
+                    16.0 * x * x * x * x * x
+                } else {
+                    let f = 2.0 * x - 2.0;
+                    0.5 * f * f * f * f * f + 1.0
+                }
+            }
+            EaseFunction::SineIn => 1.0 - (x * PI / 2.0).cos(),
+            EaseFunction::SineOut => (x * PI / 2.0).sin(),
+            EaseFunction::SineInOut => -0.5 * ((PI * x).cos() - 1.0),
+            EaseFunction::CircularIn => 1.0 - (1.0 - x * x).sqrt(),
+            EaseFunction::CircularOut => ((2.0 - x) * x).sqrt(),
+            EaseFunction::CircularInOut => {
+                if x < 0.5 {
+                    0.5 * (1.0 - (1.0 - 4.0 * x * x).sqrt())
+                } else {
+                    0.5 * ((-(2.0 * x - 3.0) * (2.0 * x - 1.0)).sqrt() + 1.0)
+                }
+            }
+            EaseFunction::ExponentialIn => {
+                if x == 0.0 {
+                    0.0
+                } else {
+                    2.0f32.powf(10.0 * (x - 1.0))
+                }
+            }
+            EaseFunction::ExponentialOut => {
+                if x == 1.0 {
+                    1.0
+                } else {
+                    1.0 - 2.0f32.powf(-10.0 * x)
+                }
+            }
+            EaseFunction::ExponentialInOut => {
+                if x == 0.0 || x == 1.0 {
+                    x
+                } else if x < 0.5 {
+                    0.5 * 2.0f32.powf(20.0 * x - 10.0)
+                } else {
+                    1.0 - 0.5 * 2.0f32.powf(-20.0 * x + 10.0)
+                }
+            }
+            EaseFunction::ElasticIn => {
+                if x == 0.0 || x == 1.0 {
+                    x
+                } else {
+                    -(2.0f32.powf(10.0 * x - 10.0)) * ((x * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin()
+                }
+            }
+            EaseFunction::ElasticOut => {
+                if x == 0.0 || x == 1.0 {
+                    x
+                } else {
+                    2.0f32.powf(-10.0 * x) * ((x * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            EaseFunction::ElasticInOut => {
+                if x == 0.0 || x == 1.0 {
+                    x
+                } else if x < 0.5 {
+                    -0.5 * 2.0f32.powf(20.0 * x - 10.0)
+                        * ((20.0 * x - 11.125) * (2.0 * PI / 4.5)).sin()
+                } else {
+                    0.5 * 2.0f32.powf(-20.0 * x + 10.0)
+                        * ((20.0 * x - 11.125) * (2.0 * PI / 4.5)).sin()
+                        + 1.0
+                }
+            }
+            EaseFunction::BackIn => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                c3 * x * x * x - c1 * x * x
+            }
+            EaseFunction::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                let f = x - 1.0;
+                1.0 + c3 * f * f * f + c1 * f * f
+            }
+            EaseFunction::BackInOut => {
+                let c1 = 1.70158;
+                let c2 = c1 * 1.525;
+                if x < 0.5 {
+                    (2.0 * x).powi(2) * ((c2 + 1.0) * 2.0 * x - c2) / 2.0
+                } else {
+                    ((2.0 * x - 2.0).powi(2) * ((c2 + 1.0) * (x * 2.0 - 2.0) + c2) + 2.0) / 2.0
+                }
+            }
+            EaseFunction::BounceIn => 1.0 - EaseFunction::BounceOut.ease(1.0 - x),
+            EaseFunction::BounceOut => {
+                let n1 = 7.5625;
+                let d1 = 2.75;
+                if x < 1.0 / d1 {
+                    n1 * x * x
+                } else if x < 2.0 / d1 {
+                    let x = x - 1.5 / d1;
+                    n1 * x * x + 0.75
+                } else if x < 2.5 / d1 {
+                    let x = x - 2.25 / d1;
+                    n1 * x * x + 0.9375
+                } else {
+                    let x = x - 2.625 / d1;
+                    n1 * x * x + 0.984375
+                }
+            }
+            EaseFunction::BounceInOut => {
+                if x < 0.5 {
+                    0.5 * EaseFunction::BounceIn.ease(2.0 * x)
+                } else {
+                    0.5 * EaseFunction::BounceOut.ease(2.0 * x - 1.0) + 0.5
+                }
+            }
+        }
+    }
+}
+
+/// Shared progress-tracking state driving the animation ratio for both
+/// [`Animator`] and [`AssetAnimator`], independent of the lens/target they
+/// apply to.
+#[derive(Debug, Clone, Copy)]
+struct AnimClock {
+    tweening_type: TweeningType,
+    ease_function: EaseFunction,
+    direction: TweeningDirection,
+    elapsed: Duration,
+}
+
+impl AnimClock {
+    fn new(tweening_type: TweeningType, ease_function: EaseFunction) -> Self {
+        AnimClock {
+            tweening_type,
+            ease_function,
+            direction: TweeningDirection::Forward,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn leg_duration(&self) -> Duration {
+        match self.tweening_type {
+            TweeningType::Once { duration } => duration,
+            TweeningType::Loop { duration, .. } => duration,
+            TweeningType::PingPong { duration, .. } => duration,
+        }
+    }
+
+    fn pause(&self) -> Option<Duration> {
+        match self.tweening_type {
+            TweeningType::Once { .. } => None,
+            TweeningType::Loop { pause, .. } => pause,
+            TweeningType::PingPong { pause, .. } => pause,
+        }
+    }
+
+    /// Advance the clock by `delta` and return the eased ratio to apply to
+    /// the lens for this tick. The linear ratio is clamped to `[0, 1]`;
+    /// overshooting curves (e.g. back, elastic) can leave that range.
+    fn tick(&mut self, delta: Duration) -> f32 {
+        let duration = self.leg_duration();
+        self.elapsed += delta;
+
+        let total = duration + self.pause().unwrap_or(Duration::ZERO);
+        if let TweeningType::Once { .. } = self.tweening_type {
+            // Hold at the end instead of wrapping back to the start.
+            self.elapsed = self.elapsed.min(duration);
+        } else if !total.is_zero() {
+            while self.elapsed >= total {
+                self.elapsed -= total;
+                if let TweeningType::PingPong { .. } = self.tweening_type {
+                    self.direction = !self.direction;
+                }
+            }
+        }
+
+        let in_pause = self.elapsed >= duration;
+        let linear_ratio = if in_pause {
+            1.0
+        } else if duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
+
+        let ratio = self.ease_function.ease(linear_ratio);
+        match self.direction {
+            TweeningDirection::Forward => ratio,
+            TweeningDirection::Backward => 1.0 - ratio,
+        }
+    }
+}
+
+/// Animator component to tween a Bevy component via a [`Lens`].
+///
+/// The animated component `T` is mutated in place by the [`component_animator_system::<T>`]
+/// system, based on the [`Lens`] the animator holds.
+#[derive(Component)]
+pub struct Animator<T: Component> {
+    state: AnimatorState,
+    clock: AnimClock,
+    lens: Box<dyn Lens<T> + Send + Sync + 'static>,
+}
+
+impl<T: Component> Animator<T> {
+    /// Create a new animator playing the given `lens` with the given easing
+    /// function and repeat behavior.
+    pub fn new(
+        ease_function: EaseFunction,
+        tweening_type: TweeningType,
+        lens: impl Lens<T> + Send + Sync + 'static,
+    ) -> Self {
+        Animator {
+            state: AnimatorState::Playing,
+            clock: AnimClock::new(tweening_type, ease_function),
+            lens: Box::new(lens),
+        }
+    }
+
+    /// Current playback state.
+    pub fn state(&self) -> AnimatorState {
+        self.state
+    }
+
+    /// Set the playback state, pausing or resuming the animation.
+    pub fn set_state(&mut self, state: AnimatorState) {
+        self.state = state;
+    }
+
+    /// Advance the animation by `delta` and apply the resulting ratio to
+    /// `target` through the lens, unless paused.
+    pub(crate) fn tick(&mut self, delta: Duration, target: &mut T) {
+        if self.state == AnimatorState::Paused {
+            return;
+        }
+        let ratio = self.clock.tick(delta);
+        self.lens.lerp(target, ratio);
+    }
+}
+
+/// Animator component to tween an [`Asset`] via a [`Lens`].
+///
+/// This does not store a [`Handle<T>`] itself; the asset
+/// to mutate is resolved from the [`Handle<T>`] component present on the same
+/// entity, so the animator and the asset it targets can never drift apart.
+#[derive(Component)]
+pub struct AssetAnimator<T: Asset> {
+    state: AnimatorState,
+    clock: AnimClock,
+    lens: Box<dyn Lens<T> + Send + Sync + 'static>,
+}
+
+impl<T: Asset> AssetAnimator<T> {
+    /// Create a new animator playing the given `lens` with the given easing
+    /// function and repeat behavior.
+    pub fn new(
+        ease_function: EaseFunction,
+        tweening_type: TweeningType,
+        lens: impl Lens<T> + Send + Sync + 'static,
+    ) -> Self {
+        AssetAnimator {
+            state: AnimatorState::Playing,
+            clock: AnimClock::new(tweening_type, ease_function),
+            lens: Box::new(lens),
+        }
+    }
+
+    /// Current playback state.
+    pub fn state(&self) -> AnimatorState {
+        self.state
+    }
+
+    /// Set the playback state, pausing or resuming the animation.
+    pub fn set_state(&mut self, state: AnimatorState) {
+        self.state = state;
+    }
+
+    /// Advance the animation by `delta` and apply the resulting ratio to
+    /// `target` through the lens, unless paused.
+    pub(crate) fn tick(&mut self, delta: Duration, target: &mut T) {
+        if self.state == AnimatorState::Paused {
+            return;
+        }
+        let ratio = self.clock.tick(delta);
+        self.lens.lerp(target, ratio);
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::{AssetAnimator, Animator};
+
+/// Plugin to add systems related to tweening of common Bevy components and
+/// assets.
+#[derive(Debug, Clone, Copy)]
+pub struct TweeningPlugin;
+
+impl Plugin for TweeningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(component_animator_system::<Transform>.system())
+            .add_system(component_animator_system::<Text>.system())
+            .add_system(component_animator_system::<Style>.system())
+            .add_system(component_animator_system::<Sprite>.system())
+            .add_system(asset_animator_system::<ColorMaterial>.system());
+    }
+}
+
+/// Ticks all [`Animator<T>`] components for a given component type `T`,
+/// applying the resulting animation to the component on the same entity.
+pub fn component_animator_system<T: Component>(
+    time: Res<Time>,
+    mut query: Query<(&mut T, &mut Animator<T>)>,
+) {
+    for (mut target, mut animator) in query.iter_mut() {
+        animator.tick(time.delta(), &mut target);
+    }
+}
+
+/// Ticks all [`AssetAnimator<T>`] components for a given asset type `T`,
+/// resolving the asset to animate from the [`Handle<T>`] component present
+/// on the same entity.
+pub fn asset_animator_system<T: Asset>(
+    time: Res<Time>,
+    mut assets: ResMut<Assets<T>>,
+    mut query: Query<(&Handle<T>, &mut AssetAnimator<T>)>,
+) {
+    for (handle, mut animator) in query.iter_mut() {
+        if let Some(target) = assets.get_mut(handle) {
+            animator.tick(time.delta(), target);
+        }
+    }
+}